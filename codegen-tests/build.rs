@@ -0,0 +1,10 @@
+include!("config.from_env.rs");
+
+fn main() {
+    let bytes = include_bytes!("../protoc-gen-from-env/tests/data/config.bin");
+    let set = <prost_types::FileDescriptorSet as prost::Message>::decode(&bytes[..]).unwrap();
+    configure(&mut prost_build::Config::new()).compile_fds(set).unwrap();
+
+    let bytes = include_bytes!("../from-env-build/tests/data/service.bin");
+    from_env_build::compile_descriptor_set(&mut prost_build::Config::new(), bytes).unwrap();
+}
@@ -0,0 +1,15 @@
+// @generated by protoc-gen-from-env from config.proto. Do not edit.
+
+/// Adds `FromEnv` to the messages in `config.proto`.
+pub fn configure(config: &mut prost_build::Config) -> &mut prost_build::Config {
+    config
+        .type_attribute(".example.DatabaseConfig", "#[derive(from_env::FromEnv)]")
+        .message_attribute(".example.DatabaseConfig", "#[from_env(prefix = \"APP_\", word_separator = \"_\")]")
+        .type_attribute(".example.ServerConfig", "#[derive(from_env::FromEnv)]")
+        .message_attribute(".example.ServerConfig", "#[from_env(prefix = \"APP_\", word_separator = \"_\")]")
+        .type_attribute(".example.AppConfig", "#[derive(from_env::FromEnv)]")
+        .message_attribute(".example.AppConfig", "#[from_env(prefix = \"APP_\", word_separator = \"_\")]")
+        .type_attribute(".example.FeatureFlags", "#[derive(from_env::FromEnv)]")
+        .message_attribute(".example.FeatureFlags", "#[from_env(prefix = \"APP_\", word_separator = \"_\")]")
+        .type_attribute(".example.LogLevel", "#[derive(from_env::FromEnv)]")
+}
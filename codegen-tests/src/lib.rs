@@ -0,0 +1,16 @@
+//! The code prost-build generates when set up the way the README describes.
+//!
+//! `config.from_env.rs` is `protoc-gen-from-env` output for
+//! `examples/config.proto` with `prefix=APP_,word_separator=_`; `build.rs`
+//! includes it and compiles `protoc-gen-from-env/tests/data/config.bin`. It
+//! also compiles `from-env-build/tests/data/service.bin` with
+//! `from_env_build::compile_descriptor_set`, so the schema options are checked
+//! by what the derive does with them.
+
+pub mod example {
+    include!(concat!(env!("OUT_DIR"), "/example.rs"));
+}
+
+pub mod service {
+    include!(concat!(env!("OUT_DIR"), "/service.rs"));
+}
@@ -0,0 +1,16 @@
+use codegen_tests::service;
+use from_env::FromEnvTrait;
+use service::gateway::{Route, Upstream};
+
+#[test]
+fn test_options_load() {
+    let gateway = service::Gateway::from_pairs([("EDGE_GW_PORT", "443"), ("EDGE_GW_UPSTREAM_HOST", "backend")]).unwrap();
+    assert_eq!(gateway.port, 443);
+    assert_eq!(gateway.upstream.unwrap().host, "backend");
+}
+
+#[test]
+fn test_nested_messages_use_their_own_options() {
+    assert_eq!(Upstream::from_pairs([("HOST", "backend")]).unwrap().host, "backend");
+    assert_eq!(Route::from_pairs([("ROUTE_PATH", "/api")]).unwrap().path, "/api");
+}
@@ -0,0 +1,42 @@
+use codegen_tests::example;
+use from_env::FromEnvTrait;
+use prost::Message;
+use prost_types::compiler::CodeGeneratorRequest;
+use prost_types::FileDescriptorSet;
+
+#[test]
+fn test_wiring_is_current() {
+    // Regenerate `config.from_env.rs` if the plugin's output changed
+    let descriptors =
+        FileDescriptorSet::decode(&include_bytes!("../../protoc-gen-from-env/tests/data/config.bin")[..]).unwrap();
+    let request = CodeGeneratorRequest {
+        file_to_generate: vec!["config.proto".to_string()],
+        parameter: Some("prefix=APP_,word_separator=_".to_string()),
+        proto_file: descriptors.file,
+        ..Default::default()
+    };
+    let response = protoc_gen_from_env::generate(&request);
+    let wiring = response
+        .file
+        .iter()
+        .find(|f| f.name() == "config.from_env.rs")
+        .unwrap();
+    assert_eq!(wiring.content(), include_str!("../config.from_env.rs"));
+}
+
+#[test]
+fn test_wiring_loads() {
+    let config = example::AppConfig::from_pairs([
+        ("APP_APP_CONFIG_APP_NAME", "demo"),
+        ("APP_APP_CONFIG_VERSION", "1.0"),
+        ("APP_APP_CONFIG_LOG_LEVEL", "debug"),
+        ("APP_APP_CONFIG_LABELS_TEAM", "core"),
+        ("APP_APP_CONFIG_CACHE", "redis_url"),
+        ("APP_APP_CONFIG_CACHE_REDIS_URL", "redis://cache"),
+    ])
+    .unwrap();
+    assert_eq!(config.app_name, "demo");
+    assert_eq!(config.log_level(), example::LogLevel::Debug);
+    assert_eq!(config.labels["team"], "core");
+    assert_eq!(config.cache, Some(example::app_config::Cache::RedisUrl("redis://cache".to_string())));
+}
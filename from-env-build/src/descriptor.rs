@@ -0,0 +1,76 @@
+//! Just enough of `google/protobuf/descriptor.proto` to read the
+//! `from_env/options.proto` extensions.
+//!
+//! Custom options are encoded as extension fields of the options messages.
+//! `prost_types` drops those as unknown fields, so these copies declare them
+//! as ordinary fields under their extension numbers instead.
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct FileDescriptorSet {
+    #[prost(message, repeated, tag = "1")]
+    pub file: Vec<FileDescriptorProto>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct FileDescriptorProto {
+    #[prost(string, optional, tag = "1")]
+    pub name: Option<String>,
+    #[prost(string, optional, tag = "2")]
+    pub package: Option<String>,
+    #[prost(message, repeated, tag = "4")]
+    pub message_type: Vec<DescriptorProto>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct DescriptorProto {
+    #[prost(string, optional, tag = "1")]
+    pub name: Option<String>,
+    #[prost(message, repeated, tag = "2")]
+    pub field: Vec<FieldDescriptorProto>,
+    #[prost(message, repeated, tag = "3")]
+    pub nested_type: Vec<DescriptorProto>,
+    #[prost(message, optional, tag = "7")]
+    pub options: Option<MessageOptions>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct FieldDescriptorProto {
+    #[prost(string, optional, tag = "1")]
+    pub name: Option<String>,
+    #[prost(message, optional, tag = "8")]
+    pub options: Option<FieldOptions>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MessageOptions {
+    #[prost(string, optional, tag = "58700")]
+    pub prefix: Option<String>,
+    #[prost(string, optional, tag = "58701")]
+    pub word_separator: Option<String>,
+    #[prost(bool, optional, tag = "58702")]
+    pub proto3_defaults: Option<bool>,
+    #[prost(string, optional, tag = "58703")]
+    pub name: Option<String>,
+    #[prost(bool, optional, tag = "58704")]
+    pub no_struct_name: Option<bool>,
+    #[prost(string, optional, tag = "58705")]
+    pub rename_all: Option<String>,
+    #[prost(string, optional, tag = "58706")]
+    pub nesting_separator: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct FieldOptions {
+    #[prost(string, optional, tag = "58710")]
+    pub env: Option<String>,
+    #[prost(string, repeated, tag = "58711")]
+    pub aliases: Vec<String>,
+    #[prost(string, repeated, tag = "58712")]
+    pub deprecated: Vec<String>,
+    #[prost(string, optional, tag = "58713")]
+    pub default: Option<String>,
+    #[prost(string, optional, tag = "58714")]
+    pub encoding: Option<String>,
+    #[prost(bool, optional, tag = "58715")]
+    pub flatten: Option<bool>,
+}
@@ -0,0 +1,262 @@
+//! `prost-build` helpers for `from-env`.
+//!
+//! [`configure`] adds `#[derive(from_env::FromEnv)]` to every generated type.
+//! [`compile_protos`] does that too, and also turns `from_env/options.proto`
+//! options in the schema into `#[from_env(...)]` attributes, so naming lives
+//! next to the messages instead of in `build.rs`:
+//!
+//! ```proto
+//! import "from_env/options.proto";
+//!
+//! message ServerConfig {
+//!   option (from_env.prefix) = "APP_";
+//!   uint32 port = 1 [(from_env.env) = "PORT"];
+//! }
+//! ```
+//!
+//! ```no_run
+//! fn main() -> std::io::Result<()> {
+//!     let mut config = prost_build::Config::new();
+//!     from_env_build::compile_protos(&mut config, &["proto/config.proto"], &["proto/"])
+//! }
+//! ```
+
+mod descriptor;
+
+use prost::Message;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const DERIVE: &str = "#[derive(from_env::FromEnv)]";
+
+/// The `.proto` file declaring the options, relative to [`include_dir`].
+pub const OPTIONS_PROTO: &str = "from_env/options.proto";
+
+/// Adds `#[derive(from_env::FromEnv)]` to every message, enum and oneof.
+pub fn configure(config: &mut prost_build::Config) -> &mut prost_build::Config {
+    config.type_attribute(".", DERIVE)
+}
+
+/// The directory holding `from_env/options.proto`. [`compile_protos`] adds it
+/// to the include path; pass it to protoc yourself when building a
+/// descriptor set for [`apply_options`].
+pub fn include_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("proto")
+}
+
+/// Where a `#[from_env(...)]` attribute goes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// A message, e.g. `.example.ServerConfig`
+    Message(String),
+    /// A field, e.g. `.example.ServerConfig.port`
+    Field(String),
+}
+
+/// The `#[from_env(...)]` attributes the options in an encoded
+/// `FileDescriptorSet` stand for, in schema order.
+pub fn attributes(descriptor_set: &[u8]) -> Result<Vec<(Target, String)>, prost::DecodeError> {
+    let set = descriptor::FileDescriptorSet::decode(descriptor_set)?;
+
+    let mut attributes = Vec::new();
+    for file in &set.file {
+        let scope = match file.package.as_deref() {
+            None | Some("") => String::new(),
+            Some(package) => format!(".{}", package),
+        };
+        for message in &file.message_type {
+            message_attributes(message, &scope, &mut attributes);
+        }
+    }
+    Ok(attributes)
+}
+
+fn message_attributes(message: &descriptor::DescriptorProto, scope: &str, attributes: &mut Vec<(Target, String)>) {
+    let path = format!("{}.{}", scope, message.name.as_deref().unwrap_or_default());
+
+    if let Some(options) = &message.options {
+        let mut args = Vec::new();
+        if let Some(prefix) = &options.prefix {
+            args.push(format!("prefix = {:?}", prefix));
+        }
+        if let Some(separator) = &options.word_separator {
+            args.push(format!("word_separator = {:?}", separator));
+        }
+        if options.proto3_defaults == Some(true) {
+            args.push("proto3_defaults".to_string());
+        }
+        if let Some(name) = &options.name {
+            args.push(format!("name = {:?}", name));
+        }
+        if options.no_struct_name == Some(true) {
+            args.push("no_struct_name".to_string());
+        }
+        if let Some(rule) = &options.rename_all {
+            args.push(format!("rename_all = {:?}", rule));
+        }
+        if let Some(separator) = &options.nesting_separator {
+            args.push(format!("nesting_separator = {:?}", separator));
+        }
+        if !args.is_empty() {
+            attributes.push((Target::Message(path.clone()), format!("#[from_env({})]", args.join(", "))));
+        }
+    }
+
+    for field in &message.field {
+        let Some(options) = &field.options else { continue };
+        let mut args = Vec::new();
+        if let Some(env) = &options.env {
+            args.push(format!("env = {:?}", env));
+        }
+        if !options.aliases.is_empty() {
+            let aliases: Vec<String> = options.aliases.iter().map(|a| format!("{:?}", a)).collect();
+            args.push(format!("aliases = [{}]", aliases.join(", ")));
+        }
+        for deprecated in &options.deprecated {
+            args.push(format!("deprecated = {:?}", deprecated));
+        }
+        if let Some(default) = &options.default {
+            args.push(format!("default = {:?}", default));
+        }
+        if let Some(encoding) = &options.encoding {
+            args.push(format!("encoding = {:?}", encoding));
+        }
+        if options.flatten == Some(true) {
+            args.push("flatten".to_string());
+        }
+        if !args.is_empty() {
+            let field_path = format!("{}.{}", path, field.name.as_deref().unwrap_or_default());
+            attributes.push((Target::Field(field_path), format!("#[from_env({})]", args.join(", "))));
+        }
+    }
+
+    for nested in &message.nested_type {
+        message_attributes(nested, &path, attributes);
+    }
+}
+
+/// Why [`apply_options`] couldn't configure prost-build.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Invalid descriptor set: {0}")]
+    Decode(#[from] prost::DecodeError),
+
+    #[error("The options on {path} would also apply to {other}")]
+    Ambiguous { path: String, other: String },
+}
+
+/// Adds the attributes from [`attributes`] to `config`.
+///
+/// prost-build applies an attribute given for `.pkg.Outer` to everything
+/// nested in it as well, so each one is registered as `pkg.Outer` instead,
+/// which only matches paths ending in it. Another path ending the same way,
+/// like `.other.pkg.Outer`, would still match; that fails with
+/// [`Error::Ambiguous`] rather than leaking the options onto it.
+pub fn apply_options<'a>(
+    config: &'a mut prost_build::Config,
+    descriptor_set: &[u8],
+) -> Result<&'a mut prost_build::Config, Error> {
+    let set = descriptor::FileDescriptorSet::decode(descriptor_set)?;
+    let mut messages = Vec::new();
+    let mut fields = Vec::new();
+    for file in &set.file {
+        let scope = match file.package.as_deref() {
+            None | Some("") => String::new(),
+            Some(package) => format!(".{}", package),
+        };
+        for message in &file.message_type {
+            collect_paths(message, &scope, &mut messages, &mut fields);
+        }
+    }
+
+    for (target, attribute) in attributes(descriptor_set)? {
+        let (path, candidates) = match &target {
+            Target::Message(path) => (path, &messages),
+            Target::Field(path) => (path, &fields),
+        };
+        let matcher = path.trim_start_matches('.');
+        let suffix = format!(".{}", matcher);
+        if let Some(other) = candidates.iter().find(|other| *other != path && other.ends_with(&suffix)) {
+            return Err(Error::Ambiguous {
+                path: path.clone(),
+                other: other.clone(),
+            });
+        }
+        match target {
+            Target::Message(_) => config.message_attribute(matcher, attribute),
+            Target::Field(_) => config.field_attribute(matcher, attribute),
+        };
+    }
+    Ok(config)
+}
+
+/// The paths of `message`, its fields and everything nested in it.
+fn collect_paths(
+    message: &descriptor::DescriptorProto,
+    scope: &str,
+    messages: &mut Vec<String>,
+    fields: &mut Vec<String>,
+) {
+    let path = format!("{}.{}", scope, message.name.as_deref().unwrap_or_default());
+    for field in &message.field {
+        fields.push(format!("{}.{}", path, field.name.as_deref().unwrap_or_default()));
+    }
+    for nested in &message.nested_type {
+        collect_paths(nested, &path, messages, fields);
+    }
+    messages.push(path);
+}
+
+/// Compiles `protos` into already configured Rust code: [`configure`], plus
+/// [`apply_options`] for any options the schema sets. Like
+/// `prost_build::Config::compile_protos`, this runs protoc, and it needs
+/// `OUT_DIR`, so call it from a build script.
+pub fn compile_protos(
+    config: &mut prost_build::Config,
+    protos: &[impl AsRef<Path>],
+    includes: &[impl AsRef<Path>],
+) -> io::Result<()> {
+    let out_dir = std::env::var_os("OUT_DIR")
+        .map(PathBuf::from)
+        .ok_or_else(|| io::Error::other("OUT_DIR is not set; call compile_protos from a build script"))?;
+    let descriptor_path = out_dir.join("from_env_descriptors.bin");
+
+    let mut protoc = Command::new(prost_build::protoc_from_env());
+    protoc
+        .arg("--include_imports")
+        .arg("--include_source_info")
+        .arg("-o")
+        .arg(&descriptor_path);
+    for include in includes {
+        protoc.arg("-I").arg(include.as_ref());
+    }
+    protoc.arg("-I").arg(include_dir());
+    for proto in protos {
+        protoc.arg(proto.as_ref());
+    }
+
+    let output = protoc.output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "protoc failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+
+    let bytes = std::fs::read(&descriptor_path)?;
+    compile_descriptor_set(config, &bytes)
+}
+
+/// [`compile_protos`] for a `FileDescriptorSet` that is already built, e.g.
+/// with `protoc --include_imports -o`.
+pub fn compile_descriptor_set(config: &mut prost_build::Config, descriptor_set: &[u8]) -> io::Result<()> {
+    configure(config);
+    apply_options(config, descriptor_set).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    // The options file only declares extensions; there's nothing to generate
+    let mut set = prost_types::FileDescriptorSet::decode(descriptor_set)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    set.file.retain(|file| file.name() != OPTIONS_PROTO);
+    config.compile_fds(set)
+}
@@ -0,0 +1,168 @@
+//! Runs the helper against `data/service.bin`, a `FileDescriptorSet` compiled
+//! from `data/service.proto`. Regenerate it after changing the schema:
+//!
+//! ```text
+//! protoc --include_imports --descriptor_set_out=from-env-build/tests/data/service.bin \
+//!        -I from-env-build/tests/data -I from-env-build/proto service.proto
+//! ```
+//!
+//! `data/ambiguous.bin` is built the same way from `data/ambiguous.proto`.
+
+use from_env_build::Target;
+
+const SERVICE: &[u8] = include_bytes!("data/service.bin");
+
+#[test]
+fn test_attributes_from_options() {
+    let attributes = from_env_build::attributes(SERVICE).unwrap();
+
+    assert_eq!(
+        attributes,
+        vec![
+            (
+                Target::Message(".service.ServerConfig".to_string()),
+                r#"#[from_env(prefix = "APP_", word_separator = "_")]"#.to_string()
+            ),
+            (
+                Target::Field(".service.ServerConfig.port".to_string()),
+                r#"#[from_env(env = "PORT", aliases = ["HTTP_PORT", "LISTEN_PORT"])]"#.to_string()
+            ),
+            (
+                Target::Field(".service.ServerConfig.workers".to_string()),
+                r#"#[from_env(deprecated = "SERVER_THREADS", default = "4")]"#.to_string()
+            ),
+            (
+                Target::Field(".service.ServerConfig.signing_key".to_string()),
+                r#"#[from_env(encoding = "hex")]"#.to_string()
+            ),
+            (
+                Target::Message(".service.Limits".to_string()),
+                "#[from_env(proto3_defaults)]".to_string()
+            ),
+            (
+                Target::Message(".service.DatabaseConfig".to_string()),
+                r#"#[from_env(name = "DB", rename_all = "SCREAMING_SNAKE")]"#.to_string()
+            ),
+            (
+                Target::Message(".service.Secrets".to_string()),
+                r#"#[from_env(prefix = "APP_", no_struct_name, nesting_separator = "__")]"#.to_string()
+            ),
+            (
+                Target::Field(".service.Secrets.limits".to_string()),
+                "#[from_env(flatten)]".to_string()
+            ),
+            (
+                Target::Message(".service.Gateway".to_string()),
+                r#"#[from_env(prefix = "EDGE_", name = "GW")]"#.to_string()
+            ),
+            (
+                Target::Message(".service.Gateway.Upstream".to_string()),
+                "#[from_env(no_struct_name)]".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_compile_descriptor_set() {
+    let out_dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("compile_descriptor_set");
+    std::fs::create_dir_all(&out_dir).unwrap();
+
+    let mut config = prost_build::Config::new();
+    config.out_dir(&out_dir);
+    from_env_build::compile_descriptor_set(&mut config, SERVICE).unwrap();
+
+    // Nothing is generated for the options file itself
+    assert!(!out_dir.join("from_env.rs").exists());
+
+    let code = std::fs::read_to_string(out_dir.join("service.rs")).unwrap();
+    assert_eq!(
+        attributes_of(&code, "pub struct ServerConfig"),
+        ["#[derive(from_env::FromEnv)]", r#"#[from_env(prefix = "APP_", word_separator = "_")]"#]
+    );
+    assert_eq!(
+        attributes_of(&code, "pub port:"),
+        [r#"#[from_env(env = "PORT", aliases = ["HTTP_PORT", "LISTEN_PORT"])]"#]
+    );
+    assert_eq!(
+        attributes_of(&code, "pub workers:"),
+        [r#"#[from_env(deprecated = "SERVER_THREADS", default = "4")]"#]
+    );
+    assert_eq!(attributes_of(&code, "pub signing_key:"), [r#"#[from_env(encoding = "hex")]"#]);
+    assert!(attributes_of(&code, "pub host:").is_empty());
+    assert_eq!(
+        attributes_of(&code, "pub struct Limits"),
+        ["#[derive(from_env::FromEnv)]", "#[from_env(proto3_defaults)]"]
+    );
+    assert_eq!(attributes_of(&code, "pub struct Plain"), ["#[derive(from_env::FromEnv)]"]);
+
+    // A message's options stay off the messages nested in it
+    assert_eq!(
+        attributes_of(&code, "pub struct Gateway"),
+        ["#[derive(from_env::FromEnv)]", r#"#[from_env(prefix = "EDGE_", name = "GW")]"#]
+    );
+    assert_eq!(
+        attributes_of(&code, "pub struct Upstream"),
+        ["#[derive(from_env::FromEnv)]", "#[from_env(no_struct_name)]"]
+    );
+    assert_eq!(attributes_of(&code, "pub struct Route"), ["#[derive(from_env::FromEnv)]"]);
+}
+
+#[test]
+fn test_ambiguous_options() {
+    // `ambiguous.proto` imports `service.proto` and declares `.other.service.Limits`
+    let mut config = prost_build::Config::new();
+    let err = from_env_build::apply_options(&mut config, include_bytes!("data/ambiguous.bin")).unwrap_err();
+    assert!(matches!(
+        &err,
+        from_env_build::Error::Ambiguous { path, other }
+            if path == ".service.Limits" && other == ".other.service.Limits"
+    ));
+    assert_eq!(
+        err.to_string(),
+        "The options on .service.Limits would also apply to .other.service.Limits"
+    );
+}
+
+#[test]
+fn test_compile_protos_needs_out_dir() {
+    // Test binaries don't get `OUT_DIR`; only build scripts do
+    let mut config = prost_build::Config::new();
+    let err = from_env_build::compile_protos(&mut config, &["data/service.proto"], &["data"]).unwrap_err();
+    assert_eq!(err.to_string(), "OUT_DIR is not set; call compile_protos from a build script");
+}
+
+/// The attributes above the item starting with `item`, leaving out prost's own.
+fn attributes_of<'a>(code: &'a str, item: &str) -> Vec<&'a str> {
+    let lines: Vec<&str> = code.lines().map(str::trim).collect();
+    let index = lines
+        .iter()
+        .position(|line| line.starts_with(item))
+        .unwrap_or_else(|| panic!("{} not found in:\n{}", item, code));
+    let mut attributes: Vec<&str> = lines[..index]
+        .iter()
+        .rev()
+        .take_while(|line| line.starts_with("#["))
+        .filter(|line| !line.starts_with("#[prost(") && !line.starts_with("#[derive(Clone"))
+        .copied()
+        .collect();
+    attributes.reverse();
+    attributes
+}
+
+#[test]
+fn test_configure_only_adds_derive() {
+    let out_dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("configure_only");
+    std::fs::create_dir_all(&out_dir).unwrap();
+
+    let mut set = <prost_types::FileDescriptorSet as prost::Message>::decode(SERVICE).unwrap();
+    set.file.retain(|file| file.name() != from_env_build::OPTIONS_PROTO);
+
+    let mut config = prost_build::Config::new();
+    config.out_dir(&out_dir);
+    from_env_build::configure(&mut config).compile_fds(set).unwrap();
+
+    let code = std::fs::read_to_string(out_dir.join("service.rs")).unwrap();
+    assert!(code.contains("#[derive(from_env::FromEnv)]"));
+    assert!(!code.contains("#[from_env("));
+}
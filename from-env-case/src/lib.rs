@@ -0,0 +1,132 @@
+//! Splitting Rust identifiers into words and respelling them for
+//! `#[from_env(rename_all = "...")]`. Shared by the derive and the protoc
+//! plugin, so the reference the plugin writes lists the names the derive reads.
+
+/// A `rename_all` spelling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenameRule {
+    /// `HTTP_SERVER2_CONFIG`
+    ScreamingSnake,
+    /// `http_server2_config`
+    Snake,
+    /// `HTTP-SERVER2-CONFIG`
+    ScreamingKebab,
+    /// `http-server2-config`
+    Kebab,
+    /// `HTTPSERVER2CONFIG`
+    UpperFlat,
+    /// `httpserver2config`
+    Flat,
+}
+
+impl RenameRule {
+    /// The names `rename_all` accepts, including serde's spellings.
+    pub fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "SCREAMING_SNAKE" | "SCREAMING_SNAKE_CASE" => RenameRule::ScreamingSnake,
+            "snake" | "snake_case" => RenameRule::Snake,
+            "SCREAMING-KEBAB" | "SCREAMING-KEBAB-CASE" => RenameRule::ScreamingKebab,
+            "kebab" | "kebab-case" => RenameRule::Kebab,
+            "UPPERFLAT" | "UPPERCASE" => RenameRule::UpperFlat,
+            "flat" | "lowercase" => RenameRule::Flat,
+            _ => return None,
+        })
+    }
+
+    /// The name `parse` reads back as this rule.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RenameRule::ScreamingSnake => "SCREAMING_SNAKE",
+            RenameRule::Snake => "snake",
+            RenameRule::ScreamingKebab => "SCREAMING-KEBAB",
+            RenameRule::Kebab => "kebab",
+            RenameRule::UpperFlat => "UPPERFLAT",
+            RenameRule::Flat => "flat",
+        }
+    }
+
+    /// What joins the words of one name.
+    fn word_separator(self) -> &'static str {
+        match self {
+            RenameRule::ScreamingSnake | RenameRule::Snake => "_",
+            RenameRule::ScreamingKebab | RenameRule::Kebab => "-",
+            RenameRule::UpperFlat | RenameRule::Flat => "",
+        }
+    }
+
+    /// What joins path segments when `nesting_separator` isn't set: the
+    /// rule's own separator, or `_` for the flat rules, which have none.
+    pub fn nesting_separator(self) -> &'static str {
+        match self.word_separator() {
+            "" => "_",
+            separator => separator,
+        }
+    }
+
+    pub fn apply(self, name: &str) -> String {
+        let separator = self.word_separator();
+        let upper = matches!(
+            self,
+            RenameRule::ScreamingSnake | RenameRule::ScreamingKebab | RenameRule::UpperFlat
+        );
+        let words = split_words(name);
+        let joined = words.join(separator);
+        if upper {
+            joined.to_uppercase()
+        } else {
+            joined.to_lowercase()
+        }
+    }
+}
+
+/// Splits an identifier into words. `_` and `-` always separate words; inside
+/// a run of letters and digits, a new word starts at an uppercase letter that
+/// follows a lowercase letter or a digit, or that ends an acronym
+/// (`HTTPServer` is `HTTP`, `Server`). Digits stay with the word before them.
+pub fn split_words(name: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+
+    for part in name.split(['_', '-']).filter(|part| !part.is_empty()) {
+        let chars: Vec<(usize, char)> = part.char_indices().collect();
+        let mut start = 0;
+        for i in 1..chars.len() {
+            let prev = chars[i - 1].1;
+            let ch = chars[i].1;
+            let next_is_lower = chars.get(i + 1).is_some_and(|(_, next)| next.is_lowercase());
+            let boundary = ch.is_uppercase()
+                && (prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_is_lower));
+            if boundary {
+                words.push(&part[start..chars[i].0]);
+                start = chars[i].0;
+            }
+        }
+        words.push(&part[start..]);
+    }
+
+    words
+}
+
+/// The struct segment without `rename_all`: upper case, with `separator`
+/// wherever a lower-case letter is followed by an upper-case one. Unlike
+/// `split_words`, acronyms and digits don't end a word here.
+pub fn to_env_case(s: &str, separator: Option<&str>) -> String {
+    let separator = separator.unwrap_or("");
+
+    let mut result = String::new();
+    let mut prev_is_lower = false;
+
+    for (i, ch) in s.chars().enumerate() {
+        if ch.is_uppercase() {
+            if i > 0 && prev_is_lower && !separator.is_empty() {
+                result.push_str(separator);
+            }
+            result.push(ch.to_ascii_uppercase());
+            prev_is_lower = false;
+        } else {
+            result.push(ch.to_ascii_uppercase());
+            prev_is_lower = ch.is_lowercase();
+        }
+    }
+
+    result
+}
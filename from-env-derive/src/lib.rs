@@ -1,16 +1,37 @@
+
+use from_env_case::{to_env_case, RenameRule};
 use proc_macro::TokenStream;
-use quote::quote;
+use quote::{format_ident, quote};
 use syn::{parse_macro_input, Data, DeriveInput, Fields, Lit};
 
 struct FromEnvConfig {
     prefix: Option<String>,
     word_separator: Option<String>,
+    /// Replaces the struct-name segment, from `#[from_env(name = "...")]`
+    name: Option<String>,
+    /// Leaves the struct-name segment out, from `#[from_env(no_struct_name)]`
+    no_struct_name: bool,
+    /// How struct and field segments are spelled, from `#[from_env(rename_all = "...")]`
+    rename_all: Option<RenameRule>,
+    /// Joins path segments (struct, field, nested field), from
+    /// `#[from_env(nesting_separator = "...")]`. Defaults to the `rename_all`
+    /// rule's separator, or `_`. Nested
+    /// structs get it from the struct being loaded instead.
+    nesting_separator: Option<String>,
+    default: bool,
+    proto3_defaults: bool,
 }
 
 impl FromEnvConfig {
     fn from_attributes(attrs: &[syn::Attribute]) -> Self {
         let mut prefix = None;
         let mut word_separator = None;
+        let mut name = None;
+        let mut no_struct_name = false;
+        let mut rename_all = None;
+        let mut nesting_separator = None;
+        let mut default = false;
+        let mut proto3_defaults = false;
 
         for attr in attrs {
             if !attr.path().is_ident("from_env") {
@@ -30,6 +51,37 @@ impl FromEnvConfig {
                     if let Lit::Str(lit_str) = s {
                         word_separator = Some(lit_str.value());
                     }
+                } else if meta.path.is_ident("name") {
+                    let value = meta.value()?;
+                    let s: Lit = value.parse()?;
+                    if let Lit::Str(lit_str) = s {
+                        name = Some(lit_str.value());
+                    }
+                } else if meta.path.is_ident("no_struct_name") {
+                    no_struct_name = true;
+                } else if meta.path.is_ident("rename_all") {
+                    let value = meta.value()?;
+                    let s: Lit = value.parse()?;
+                    if let Lit::Str(lit_str) = s {
+                        let rule = lit_str.value();
+                        rename_all = Some(RenameRule::parse(&rule).unwrap_or_else(|| {
+                            panic!(
+                                "unknown rename_all rule '{}', expected one of SCREAMING_SNAKE, snake, \
+                                 SCREAMING-KEBAB, kebab, UPPERFLAT or flat",
+                                rule
+                            )
+                        }));
+                    }
+                } else if meta.path.is_ident("nesting_separator") {
+                    let value = meta.value()?;
+                    let s: Lit = value.parse()?;
+                    if let Lit::Str(lit_str) = s {
+                        nesting_separator = Some(lit_str.value());
+                    }
+                } else if meta.path.is_ident("default") {
+                    default = true;
+                } else if meta.path.is_ident("proto3_defaults") {
+                    proto3_defaults = true;
                 }
                 Ok(())
             });
@@ -38,30 +90,164 @@ impl FromEnvConfig {
         FromEnvConfig {
             prefix,
             word_separator,
+            name,
+            no_struct_name,
+            rename_all,
+            nesting_separator,
+            default,
+            proto3_defaults,
         }
     }
 }
 
-fn to_env_case(s: &str, separator: Option<&str>) -> String {
-    let separator = separator.unwrap_or("");
+struct FieldConfig {
+    env: Option<String>,
+    aliases: Vec<String>,
+    deprecated: Vec<String>,
+    default: Option<String>,
+    default_fn: Option<syn::Path>,
+    /// The enum named by prost's `#[prost(enumeration = "...")]`
+    enumeration: Option<syn::Path>,
+    /// Set for fields tagged with prost's `#[prost(oneof = "...")]`
+    oneof: bool,
+    /// How a bytes field is written, from `#[from_env(encoding = "...")]`
+    encoding: Option<String>,
+    /// Loads a nested struct's fields under the parent's prefix, from
+    /// `#[from_env(flatten)]`
+    flatten: bool,
+}
+
+impl FieldConfig {
+    fn from_attributes(attrs: &[syn::Attribute]) -> Self {
+        let mut env = None;
+        let mut aliases = Vec::new();
+        let mut deprecated = Vec::new();
+        let mut default = None;
+        let mut default_fn = None;
+        let mut enumeration = None;
+        let mut oneof = false;
+        let mut encoding = None;
+        let mut flatten = false;
 
-    let mut result = String::new();
-    let mut prev_is_lower = false;
+        for attr in attrs {
+            if attr.path().is_ident("prost") {
+                let _ = attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("enumeration") {
+                        let value = meta.value()?;
+                        let s: Lit = value.parse()?;
+                        if let Lit::Str(lit_str) = s {
+                            enumeration = Some(lit_str.parse()?);
+                        }
+                    } else if meta.path.is_ident("oneof") {
+                        oneof = true;
+                        meta.value()?.parse::<Lit>()?;
+                    } else if meta.input.peek(syn::Token![=]) {
+                        // Other prost keys like `tag = "1"`
+                        meta.value()?.parse::<Lit>()?;
+                    }
+                    Ok(())
+                });
+                continue;
+            }
 
-    for (i, ch) in s.chars().enumerate() {
-        if ch.is_uppercase() {
-            if i > 0 && prev_is_lower && !separator.is_empty() {
-                result.push_str(separator);
+            if !attr.path().is_ident("from_env") {
+                continue;
             }
-            result.push(ch.to_ascii_uppercase());
-            prev_is_lower = false;
+
+            let _ = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("env") {
+                    let value = meta.value()?;
+                    let s: Lit = value.parse()?;
+                    if let Lit::Str(lit_str) = s {
+                        env = Some(lit_str.value());
+                    }
+                } else if meta.path.is_ident("aliases") {
+                    let value = meta.value()?;
+                    let array: syn::ExprArray = value.parse()?;
+                    for elem in array.elems {
+                        if let syn::Expr::Lit(syn::ExprLit { lit: Lit::Str(lit_str), .. }) = elem {
+                            aliases.push(lit_str.value());
+                        }
+                    }
+                } else if meta.path.is_ident("deprecated") {
+                    let value = meta.value()?;
+                    let s: Lit = value.parse()?;
+                    if let Lit::Str(lit_str) = s {
+                        deprecated.push(lit_str.value());
+                    }
+                } else if meta.path.is_ident("default") {
+                    let value = meta.value()?;
+                    let s: Lit = value.parse()?;
+                    if let Lit::Str(lit_str) = s {
+                        default = Some(lit_str.value());
+                    }
+                } else if meta.path.is_ident("default_fn") {
+                    let value = meta.value()?;
+                    let s: Lit = value.parse()?;
+                    if let Lit::Str(lit_str) = s {
+                        default_fn = Some(lit_str.parse()?);
+                    }
+                } else if meta.path.is_ident("encoding") {
+                    let value = meta.value()?;
+                    let s: Lit = value.parse()?;
+                    if let Lit::Str(lit_str) = s {
+                        encoding = Some(lit_str.value());
+                    }
+                } else if meta.path.is_ident("flatten") {
+                    flatten = true;
+                }
+                Ok(())
+            });
+        }
+
+        FieldConfig {
+            env,
+            aliases,
+            deprecated,
+            default,
+            default_fn,
+            enumeration,
+            oneof,
+            encoding,
+            flatten,
+        }
+    }
+}
+
+/// Where a field's value comes from when its variable isn't set.
+enum FieldDefault {
+    /// No default, the field is required
+    None,
+    /// `#[from_env(default = "...")]`
+    Literal(String),
+    /// `#[from_env(default_fn = "...")]`
+    Fn(syn::Path),
+    /// The type's zero value under `#[from_env(proto3_defaults)]`
+    Proto3,
+    /// The struct's `Default` impl under `#[from_env(default)]`
+    Struct,
+}
+
+impl FieldDefault {
+    fn new(config: &FromEnvConfig, field_config: &FieldConfig, field_type: &syn::Type) -> Self {
+        if let Some(default) = &field_config.default {
+            FieldDefault::Literal(default.clone())
+        } else if let Some(default_fn) = &field_config.default_fn {
+            FieldDefault::Fn(default_fn.clone())
+        } else if config.proto3_defaults && has_proto3_zero_value(field_type) {
+            FieldDefault::Proto3
+        } else if config.default {
+            FieldDefault::Struct
         } else {
-            result.push(ch.to_ascii_uppercase());
-            prev_is_lower = ch.is_lowercase();
+            FieldDefault::None
         }
     }
+}
 
-    result
+/// Scalars, `repeated` and `map` fields have an implicit zero value in proto3;
+/// message fields (`Option<T>` in prost) don't.
+fn has_proto3_zero_value(field_type: &syn::Type) -> bool {
+    is_single_var(field_type) || extract_inner_type(field_type, "Vec").is_some() || extract_map_types(field_type).is_some()
 }
 
 #[proc_macro_derive(FromEnv, attributes(from_env))]
@@ -70,54 +256,389 @@ pub fn derive_from_env(input: TokenStream) -> TokenStream {
     let name = &input.ident;
     let config = FromEnvConfig::from_attributes(&input.attrs);
 
-    let struct_name_env = to_env_case(&name.to_string(), config.word_separator.as_deref());
-
     let fields = match &input.data {
         Data::Struct(data) => match &data.fields {
             Fields::Named(fields) => &fields.named,
             _ => panic!("FromEnv only supports structs with named fields"),
         },
-        _ => panic!("FromEnv can only be derived for structs"),
+        Data::Enum(data) => return derive_from_env_enum(name, data),
+        _ => panic!("FromEnv can only be derived for structs and enums"),
     };
 
+    // Everything up to and including the struct segment, e.g. `APP_SERVERCONFIG_`.
+    // The runtime prefix passed to `from_source_with_prefix` is prepended to this.
+    if config.rename_all.is_some() && config.word_separator.is_some() {
+        panic!("`rename_all` and `word_separator` can't be used together");
+    }
+    let nesting_separator = config
+        .nesting_separator
+        .as_deref()
+        .unwrap_or_else(|| config.rename_all.map_or("_", RenameRule::nesting_separator));
+    let struct_segment = match (&config.name, config.no_struct_name) {
+        (Some(_), true) => panic!("`name` and `no_struct_name` can't be used together"),
+        (Some(struct_name), false) => format!("{}{}", struct_name, nesting_separator),
+        (None, true) => String::new(),
+        (None, false) => match config.rename_all {
+            Some(rule) => format!("{}{}", rule.apply(&name.to_string()), nesting_separator),
+            None => format!(
+                "{}{}",
+                to_env_case(&name.to_string(), config.word_separator.as_deref()),
+                nesting_separator
+            ),
+        },
+    };
+    let struct_prefix = format!("{}{}", config.prefix.as_deref().unwrap_or(""), struct_segment);
+
     let mut from_env_assignments = Vec::new();
     let mut load_from_env_assignments = Vec::new();
+    let mut env_vars_pushes = Vec::new();
+    let mut collect_lets = Vec::new();
+    let mut collect_assignments = Vec::new();
+    let mut field_vars = Vec::new();
+    let mut has_map = false;
 
     for field in fields {
         let field_name = field.ident.as_ref().unwrap();
         let field_type = &field.ty;
         let field_name_str = field_name.to_string();
-        let field_name_env = field_name_str.to_uppercase();
+        let field_name_env = match config.rename_all {
+            Some(rule) => rule.apply(&field_name_str),
+            None => field_name_str.to_uppercase(),
+        };
+        let field_config = FieldConfig::from_attributes(&field.attrs);
+
+        // `env = "..."` is the exact name, ignoring both the struct-level and
+        // the runtime prefix
+        let env_var_expr = match &field_config.env {
+            Some(env) => quote!(#env),
+            None => quote!(&format!("{}{}", prefix, #field_name_env)),
+        };
 
-        let env_var_name = if let Some(prefix) = &config.prefix {
-            format!("{}{}_{}", prefix, struct_name_env, field_name_env)
+        // Binds `env_var_name` to the name the field is read from, picking
+        // among aliases and deprecated names by what the source defines
+        let name_binding = |report: bool| {
+            if field_config.aliases.is_empty() && field_config.deprecated.is_empty() {
+                return quote! { let env_var_name: &str = #env_var_expr; };
+            }
+            let aliases = &field_config.aliases;
+            let deprecated = &field_config.deprecated;
+            quote! {
+                let env_var_name: &str = #env_var_expr;
+                let env_var_name: &str = from_env::__private::resolve_name(
+                    source,
+                    env_var_name,
+                    &[#(#aliases),*],
+                    &[#(#deprecated),*],
+                    #report,
+                );
+            }
+        };
+        if field_config.flatten
+            && (field_config.env.is_some()
+                || !field_config.aliases.is_empty()
+                || !field_config.deprecated.is_empty()
+                || field_config.default.is_some()
+                || field_config.default_fn.is_some()
+                || field_config.encoding.is_some())
+        {
+            panic!("`flatten` fields have no variable of their own, so they can't take env, aliases, deprecated, default, default_fn or encoding");
+        }
+
+        // A flattened field has no name of its own to bind
+        let (load_name_binding, probe_name_binding) = if field_config.flatten {
+            (quote! {}, quote! {})
         } else {
-            format!("{}_{}", struct_name_env, field_name_env)
+            field_vars.push(quote! { (#env_var_expr).to_string() });
+            (name_binding(true), name_binding(false))
         };
+        has_map |= !field_config.flatten && !field_config.oneof && is_map_type(field_type);
 
-        let parse_expr = generate_parse_expr(field_type, &env_var_name);
+        let env_var_name = quote!(env_var_name);
+        let (parse_expr, collect_expr, vars_expr) = if field_config.flatten {
+            generate_flatten_exprs(field_type)
+        } else if field_config.oneof {
+            // The discriminator is the field's own variable; the chosen
+            // variant's payload lives underneath it
+            let parse_expr = generate_oneof_parse_expr(field_type, &env_var_name);
+            let collect_expr = generate_closure_collect_expr(field_type, &parse_expr);
+            let required = extract_inner_type(field_type, "Option").is_none();
+            (parse_expr, collect_expr, quote! { vec![(#env_var_name.to_string(), #required)] })
+        } else {
+            let parse_expr = match (&field_config.enumeration, &field_config.encoding) {
+                (Some(enum_path), _) => generate_enumeration_parse_expr(field_type, enum_path, &env_var_name),
+                (None, Some(encoding)) => generate_bytes_parse_expr(field_type, encoding, &env_var_name)
+                    .unwrap_or_else(|| panic!("`encoding` only applies to Vec<u8> and Bytes fields")),
+                (None, None) => generate_parse_expr(field_type, &env_var_name),
+            };
+            let collect_expr = generate_collect_expr(field_type, &env_var_name, &parse_expr);
+            let vars_expr = generate_vars_expr(field_type, &env_var_name);
+            (parse_expr, collect_expr, vars_expr)
+        };
+
+        // A field is missing when none of the variables it reads are set
+        let missing_expr = quote! {
+            (#vars_expr).iter().all(|(name, _)| source.get(name).is_none())
+        };
+
+        let field_default = FieldDefault::new(&config, &field_config, field_type);
+
+        // `(from_env value, collect value)` for the field, applying its default
+        let (value_expr, collect_value_expr) = match &field_default {
+            FieldDefault::Literal(default) => {
+                // The default stands in for the missing variable, so it goes
+                // through the field's own parser
+                let with_default = quote! {
+                    let source: &dyn from_env::EnvSource =
+                        &from_env::__private::WithDefault::new(source, env_var_name, #default);
+                };
+                (
+                    quote! { #with_default #parse_expr },
+                    quote! { #with_default #collect_expr },
+                )
+            }
+            FieldDefault::Fn(default_fn) => (
+                quote! { if #missing_expr { #default_fn() } else { #parse_expr } },
+                quote! { if #missing_expr { Some(#default_fn()) } else { #collect_expr } },
+            ),
+            FieldDefault::Proto3 => (
+                quote! { if #missing_expr { <#field_type as Default>::default() } else { #parse_expr } },
+                quote! { if #missing_expr { Some(<#field_type as Default>::default()) } else { #collect_expr } },
+            ),
+            FieldDefault::Struct => (
+                quote! { if #missing_expr { struct_defaults.#field_name } else { #parse_expr } },
+                quote! { if #missing_expr { Some(struct_defaults.#field_name) } else { #collect_expr } },
+            ),
+            FieldDefault::None => (parse_expr.clone(), collect_expr.clone()),
+        };
 
         from_env_assignments.push(quote! {
-            #field_name: #parse_expr,
+            #field_name: {
+                #load_name_binding
+                #value_expr
+            },
         });
 
-        load_from_env_assignments.push(quote! {
-            self.#field_name = #parse_expr;
+        // Loading into an existing instance keeps its current value for
+        // missing fields when the struct-level `default` applies
+        if let FieldDefault::Struct = field_default {
+            load_from_env_assignments.push(quote! {
+                {
+                    #load_name_binding
+                    if !#missing_expr {
+                        self.#field_name = #parse_expr;
+                    }
+                }
+            });
+        } else {
+            load_from_env_assignments.push(quote! {
+                self.#field_name = {
+                    #load_name_binding
+                    #value_expr
+                };
+            });
+        }
+
+        let field_local = format_ident!("field_{}", field_name);
+        collect_lets.push(quote! {
+            let #field_local = {
+                #load_name_binding
+                #collect_value_expr
+            };
+        });
+        collect_assignments.push(quote! {
+            #field_name: #field_local?,
+        });
+
+        // Fields with a default are never required
+        let vars_expr = if !matches!(field_default, FieldDefault::None) {
+            quote! { (#vars_expr).into_iter().map(|(name, _)| (name, false)) }
+        } else {
+            vars_expr
+        };
+        env_vars_pushes.push(quote! {
+            {
+                #probe_name_binding
+                vars.extend(#vars_expr);
+            }
         });
     }
 
+    let struct_defaults = if config.default {
+        quote! { let struct_defaults = <Self as Default>::default(); }
+    } else {
+        quote! {}
+    };
+
+    // Map fields scan for `{field}_<KEY>` and skip their siblings' variables
+    let field_vars = if has_map {
+        quote! { let field_vars: &[String] = &[#(#field_vars),*]; }
+    } else {
+        quote! {}
+    };
+
     let expanded = quote! {
         impl from_env::FromEnvTrait for #name {
-            fn from_env() -> Result<Self, from_env::FromEnvError> {
+            fn from_source_with_prefix(
+                source: &impl from_env::EnvSource,
+                prefix: &str,
+            ) -> Result<Self, from_env::FromEnvError> {
+                <Self as from_env::FromEnvTrait>::from_env_with_prefix_internal(
+                    source,
+                    &format!("{}{}", prefix, #struct_prefix),
+                    #nesting_separator,
+                )
+                .map_err(|e| from_env::__private::locate(source, e))
+            }
+
+            fn load_from_source_with_prefix(
+                &mut self,
+                source: &impl from_env::EnvSource,
+                prefix: &str,
+            ) -> Result<(), from_env::FromEnvError> {
+                <Self as from_env::FromEnvTrait>::load_from_env_with_prefix_internal(
+                    self,
+                    source,
+                    &format!("{}{}", prefix, #struct_prefix),
+                    #nesting_separator,
+                )
+                .map_err(|e| from_env::__private::locate(source, e))
+            }
+
+            fn from_source_with_prefix_all_errors(
+                source: &impl from_env::EnvSource,
+                prefix: &str,
+            ) -> Result<Self, from_env::FromEnvErrors> {
+                let mut errors = Vec::new();
+                let value = <Self as from_env::FromEnvTrait>::from_env_with_prefix_collect(
+                    source,
+                    &format!("{}{}", prefix, #struct_prefix),
+                    #nesting_separator,
+                    &mut errors,
+                );
+                from_env::__private::all_errors(source, value, errors)
+            }
+
+            fn from_env_with_prefix_internal(
+                source: &dyn from_env::EnvSource,
+                prefix: &str,
+                nesting_separator: &str,
+            ) -> Result<Self, from_env::FromEnvError> {
+                #field_vars
+                #struct_defaults
                 Ok(Self {
                     #(#from_env_assignments)*
                 })
             }
 
-            fn load_from_env(&mut self) -> Result<(), from_env::FromEnvError> {
+            fn load_from_env_with_prefix_internal(
+                &mut self,
+                source: &dyn from_env::EnvSource,
+                prefix: &str,
+                nesting_separator: &str,
+            ) -> Result<(), from_env::FromEnvError> {
+                #field_vars
                 #(#load_from_env_assignments)*
                 Ok(())
             }
+
+            // Each field is wrapped as `Ok(parse_expr)`, and some parse
+            // expressions end in `?` on a `Result` of the right type already.
+            #[allow(clippy::needless_question_mark)]
+            fn from_env_with_prefix_collect(
+                source: &dyn from_env::EnvSource,
+                prefix: &str,
+                nesting_separator: &str,
+                errors: &mut Vec<from_env::FromEnvError>,
+            ) -> Option<Self> {
+                #field_vars
+                #struct_defaults
+                #(#collect_lets)*
+                Some(Self {
+                    #(#collect_assignments)*
+                })
+            }
+
+            fn env_vars_with_prefix_internal(
+                source: &dyn from_env::EnvSource,
+                prefix: &str,
+                nesting_separator: &str,
+            ) -> Vec<(String, bool)> {
+                #field_vars
+                let mut vars = Vec::new();
+                #(#env_vars_pushes)*
+                vars
+            }
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
+/// Enums come from prost via `type_attribute(".", ...)`, which also reaches
+/// enums. A oneof (every variant wraps one value) gets a `FromEnvOneof` impl.
+/// A proto enum (unit variants only) is read through the `i32` fields tagged
+/// `#[prost(enumeration = "...")]`; it only lists its variants, so errors can
+/// name them.
+fn derive_from_env_enum(name: &syn::Ident, data: &syn::DataEnum) -> TokenStream {
+    if data.variants.iter().all(|variant| matches!(variant.fields, Fields::Unit)) {
+        let variants = data.variants.iter().map(|variant| &variant.ident);
+        let expanded = quote! {
+            impl from_env::__private::ProstEnum for #name {
+                const VARIANTS: &'static [Self] = &[#(#name::#variants),*];
+            }
+        };
+        return TokenStream::from(expanded);
+    }
+
+    let enum_name = name.to_string();
+    let mut arms = Vec::new();
+    let mut valid = Vec::new();
+
+    for variant in &data.variants {
+        let payload_type = match &variant.fields {
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => &fields.unnamed[0].ty,
+            _ => panic!("FromEnv on enums only supports oneofs, whose variants each wrap a single value"),
+        };
+        let variant_name = &variant.ident;
+        let variant_env = to_env_case(&variant_name.to_string(), Some("_"));
+        let parse_expr = generate_parse_expr(payload_type, &quote!(env_var_name));
+        // A map payload is the only thing under its variable
+        let field_vars = if is_map_type(payload_type) {
+            quote! { let field_vars: &[String] = &[]; }
+        } else {
+            quote! {}
+        };
+
+        arms.push(quote! {
+            #variant_env => {
+                let env_var_name: &str = &format!("{}{}{}", discriminator, nesting_separator, #variant_env);
+                #field_vars
+                Ok(#name::#variant_name(#parse_expr))
+            }
+        });
+        valid.push(variant_env);
+    }
+
+    let expanded = quote! {
+        impl from_env::FromEnvOneof for #name {
+            fn from_env_variant_internal(
+                source: &dyn from_env::EnvSource,
+                discriminator: &str,
+                nesting_separator: &str,
+                value: &str,
+            ) -> Result<Self, from_env::FromEnvError> {
+                match value.trim().to_uppercase().replace('-', "_").as_str() {
+                    #(#arms)*
+                    _ => Err(from_env::FromEnvError::ParseError {
+                        var: discriminator.to_string(),
+                        source: from_env::ParseError::UnknownVariant {
+                            oneof: #enum_name,
+                            value: value.to_string(),
+                            valid: vec![#(#valid.to_string()),*],
+                        },
+                    }),
+                }
+            }
         }
     };
 
@@ -139,14 +660,61 @@ fn extract_inner_type<'a>(ty: &'a syn::Type, wrapper: &str) -> Option<&'a syn::T
     None
 }
 
-fn generate_parse_expr(field_type: &syn::Type, env_var_name: &str) -> proc_macro2::TokenStream {
+/// The key and value types of a `HashMap<K, V>` or `BTreeMap<K, V>`.
+fn extract_map_types(ty: &syn::Type) -> Option<(&syn::Type, &syn::Type)> {
+    if let syn::Type::Path(type_path) = ty {
+        if let Some(segment) = type_path.path.segments.last() {
+            if segment.ident == "HashMap" || segment.ident == "BTreeMap" {
+                if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+                    let mut types = args.args.iter().filter_map(|arg| match arg {
+                        syn::GenericArgument::Type(ty) => Some(ty),
+                        _ => None,
+                    });
+                    if let (Some(key_type), Some(value_type)) = (types.next(), types.next()) {
+                        return Some((key_type, value_type));
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Whether `ty` is a map or an optional map.
+fn is_map_type(ty: &syn::Type) -> bool {
+    extract_map_types(extract_inner_type(ty, "Option").unwrap_or(ty)).is_some()
+}
+
+/// Loads a map field as `Option<Map>` via `from_env::__private::map`. The
+/// expression refers to a `field_vars` local, which keeps sibling fields out
+/// of the scanned entries.
+fn generate_map_parse_expr(map_type: &syn::Type, env_var_name: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let (key_type, value_type) = extract_map_types(map_type).unwrap();
+    quote! {
+        from_env::__private::map::<#map_type, #key_type, #value_type>(source, #env_var_name, nesting_separator, field_vars)?
+    }
+}
+
+fn generate_parse_expr(field_type: &syn::Type, env_var_name: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    // Proto `bytes` fields default to base64
+    if let Some(bytes_parse) = generate_bytes_parse_expr(field_type, "base64", env_var_name) {
+        return bytes_parse;
+    }
+
     // Check for Option<T>
     if let Some(inner_type) = extract_inner_type(field_type, "Option") {
+        if extract_map_types(inner_type).is_some() {
+            return generate_map_parse_expr(inner_type, env_var_name);
+        }
+        if !is_primitive(inner_type) {
+            let dispatch = generate_dispatch_expr(inner_type, quote!(optional(source, #env_var_name)));
+            return quote! { #dispatch? };
+        }
         let inner_parse = generate_required_parse_expr(inner_type, env_var_name);
         return quote! {
-            match std::env::var(#env_var_name) {
-                Ok(_) => Some(#inner_parse),
-                Err(_) => None,
+            match source.get(#env_var_name) {
+                Some(_) => Some(#inner_parse),
+                None => None,
             }
         };
     }
@@ -156,26 +724,34 @@ fn generate_parse_expr(field_type: &syn::Type, env_var_name: &str) -> proc_macro
         return generate_vec_parse_expr(inner_type, env_var_name);
     }
 
+    // Check for HashMap<K, V> and BTreeMap<K, V>
+    if extract_map_types(field_type).is_some() {
+        let map_parse = generate_map_parse_expr(field_type, env_var_name);
+        return quote! {
+            #map_parse
+                .ok_or_else(|| from_env::FromEnvError::MissingVariable(#env_var_name.to_string()))?
+        };
+    }
+
     // Regular required field
     generate_required_parse_expr(field_type, env_var_name)
 }
 
-fn generate_required_parse_expr(field_type: &syn::Type, env_var_name: &str) -> proc_macro2::TokenStream {
-    let type_str = quote!(#field_type).to_string();
-    let type_str = type_str.replace(" ", "");
+fn generate_required_parse_expr(field_type: &syn::Type, env_var_name: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let type_str = simple_type_name(field_type);
 
     match type_str.as_str() {
         "String" => {
             quote! {
-                std::env::var(#env_var_name)
-                    .map_err(|_| from_env::FromEnvError::MissingVariable(#env_var_name.to_string()))?
+                source.get(#env_var_name)
+                    .ok_or_else(|| from_env::FromEnvError::MissingVariable(#env_var_name.to_string()))?
             }
         }
         "bool" => {
             quote! {
                 {
-                    let value = std::env::var(#env_var_name)
-                        .map_err(|_| from_env::FromEnvError::MissingVariable(#env_var_name.to_string()))?;
+                    let value = source.get(#env_var_name)
+                        .ok_or_else(|| from_env::FromEnvError::MissingVariable(#env_var_name.to_string()))?;
                     from_env::parse_bool(&value)
                         .map_err(|e| from_env::FromEnvError::ParseError {
                             var: #env_var_name.to_string(),
@@ -187,8 +763,8 @@ fn generate_required_parse_expr(field_type: &syn::Type, env_var_name: &str) -> p
         "char" => {
             quote! {
                 {
-                    let value = std::env::var(#env_var_name)
-                        .map_err(|_| from_env::FromEnvError::MissingVariable(#env_var_name.to_string()))?;
+                    let value = source.get(#env_var_name)
+                        .ok_or_else(|| from_env::FromEnvError::MissingVariable(#env_var_name.to_string()))?;
                     from_env::parse_char(&value)
                         .map_err(|e| from_env::FromEnvError::ParseError {
                             var: #env_var_name.to_string(),
@@ -201,8 +777,8 @@ fn generate_required_parse_expr(field_type: &syn::Type, env_var_name: &str) -> p
         "u8" | "u16" | "u32" | "u64" | "u128" | "usize" => {
             quote! {
                 {
-                    let value = std::env::var(#env_var_name)
-                        .map_err(|_| from_env::FromEnvError::MissingVariable(#env_var_name.to_string()))?;
+                    let value = source.get(#env_var_name)
+                        .ok_or_else(|| from_env::FromEnvError::MissingVariable(#env_var_name.to_string()))?;
                     value.parse::<#field_type>()
                         .map_err(|e| from_env::FromEnvError::ParseError {
                             var: #env_var_name.to_string(),
@@ -214,8 +790,8 @@ fn generate_required_parse_expr(field_type: &syn::Type, env_var_name: &str) -> p
         "f32" | "f64" => {
             quote! {
                 {
-                    let value = std::env::var(#env_var_name)
-                        .map_err(|_| from_env::FromEnvError::MissingVariable(#env_var_name.to_string()))?;
+                    let value = source.get(#env_var_name)
+                        .ok_or_else(|| from_env::FromEnvError::MissingVariable(#env_var_name.to_string()))?;
                     value.parse::<#field_type>()
                         .map_err(|e| from_env::FromEnvError::ParseError {
                             var: #env_var_name.to_string(),
@@ -225,32 +801,309 @@ fn generate_required_parse_expr(field_type: &syn::Type, env_var_name: &str) -> p
             }
         }
         _ => {
-            // For any other type, try to use FromStr
+            // Nested FromEnv struct, or any other type via FromStr
+            let dispatch = generate_dispatch_expr(field_type, quote!(required(source, #env_var_name)));
+            quote! { #dispatch? }
+        }
+    }
+}
+
+/// Loads a field for `from_env_with_prefix_collect`, yielding `Option<T>` and
+/// pushing any failure onto `errors`. Nested structs collect their own fields'
+/// errors rather than stopping at the first.
+fn generate_collect_expr(
+    field_type: &syn::Type,
+    env_var_name: &proc_macro2::TokenStream,
+    parse_expr: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    if let Some(inner_type) = extract_inner_type(field_type, "Option") {
+        if !is_single_var(inner_type) && extract_map_types(inner_type).is_none() {
+            return generate_dispatch_expr(inner_type, quote!(optional_collect(source, #env_var_name, errors)));
+        }
+    } else if let Some(inner_type) = extract_inner_type(field_type, "Vec") {
+        if !is_single_var(inner_type) {
+            return generate_dispatch_expr(inner_type, quote!(vec_collect(source, #env_var_name, errors)));
+        }
+    } else if extract_map_types(field_type).is_none() && !is_single_var(field_type) {
+        return generate_dispatch_expr(field_type, quote!(required_collect(source, #env_var_name, errors)));
+    }
+
+    generate_closure_collect_expr(field_type, parse_expr)
+}
+
+/// Runs a fail-fast parse expression for `from_env_with_prefix_collect`.
+fn generate_closure_collect_expr(
+    field_type: &syn::Type,
+    parse_expr: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    quote! {
+        from_env::__private::collect(
+            (|| -> Result<#field_type, from_env::FromEnvError> { Ok(#parse_expr) })(),
+            errors,
+        )
+    }
+}
+
+/// `(parse, collect, vars)` expressions for a `#[from_env(flatten)]` field,
+/// `T` or `Option<T>` where `T: FromEnvTrait`. Its fields are read from the
+/// parent's own `prefix`.
+fn generate_flatten_exprs(
+    field_type: &syn::Type,
+) -> (proc_macro2::TokenStream, proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    match extract_inner_type(field_type, "Option") {
+        Some(inner_type) => (
+            quote! { from_env::__private::flatten_optional::<#inner_type>(source, prefix, nesting_separator)? },
+            quote! { from_env::__private::flatten_optional_collect::<#inner_type>(source, prefix, nesting_separator, errors) },
             quote! {
-                {
-                    let value = std::env::var(#env_var_name)
-                        .map_err(|_| from_env::FromEnvError::MissingVariable(#env_var_name.to_string()))?;
-                    value.parse::<#field_type>()
-                        .map_err(|_| from_env::FromEnvError::ParseError {
-                            var: #env_var_name.to_string(),
-                            source: from_env::ParseError::InvalidUtf8,
-                        })?
-                }
+                <#inner_type as from_env::FromEnvTrait>::env_vars_with_prefix_internal(source, prefix, nesting_separator)
+                    .into_iter()
+                    .map(|(name, _)| (name, false))
+                    .collect::<Vec<_>>()
+            },
+        ),
+        None => (
+            quote! { <#field_type as from_env::FromEnvTrait>::from_env_with_prefix_internal(source, prefix, nesting_separator)? },
+            quote! { <#field_type as from_env::FromEnvTrait>::from_env_with_prefix_collect(source, prefix, nesting_separator, errors) },
+            quote! { <#field_type as from_env::FromEnvTrait>::env_vars_with_prefix_internal(source, prefix, nesting_separator) },
+        ),
+    }
+}
+
+/// Loads a prost oneof field, `Option<Kind>` (or a required `Kind`), by reading
+/// the variant name from the field's variable.
+fn generate_oneof_parse_expr(field_type: &syn::Type, env_var_name: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    if let Some(inner_type) = extract_inner_type(field_type, "Option") {
+        return quote! {
+            match source.get(#env_var_name) {
+                Some(value) => Some(
+                    <#inner_type as from_env::FromEnvOneof>::from_env_variant_internal(source, #env_var_name, nesting_separator, &value)?
+                ),
+                None => None,
             }
+        };
+    }
+
+    quote! {
+        {
+            let value = source.get(#env_var_name)
+                .ok_or_else(|| from_env::FromEnvError::MissingVariable(#env_var_name.to_string()))?;
+            <#field_type as from_env::FromEnvOneof>::from_env_variant_internal(source, #env_var_name, nesting_separator, &value)?
         }
     }
 }
 
-fn generate_vec_parse_expr(inner_type: &syn::Type, env_var_name: &str) -> proc_macro2::TokenStream {
-    let type_str = quote!(#inner_type).to_string();
-    let type_str = type_str.replace(" ", "");
+/// The `(name, required)` pairs a field contributes to `env_vars_with_prefix_internal`.
+fn generate_vars_expr(field_type: &syn::Type, env_var_name: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    if let Some(inner_type) = extract_inner_type(field_type, "Option") {
+        if extract_map_types(inner_type).is_some() {
+            return quote! { from_env::__private::map_vars(source, #env_var_name, nesting_separator, field_vars, false) };
+        }
+        if !is_single_var(inner_type) {
+            let dispatch = generate_dispatch_expr(inner_type, quote!(vars(source, #env_var_name, false)));
+            return quote! { #dispatch };
+        }
+        return quote! { vec![(#env_var_name.to_string(), false)] };
+    }
+
+    if let Some(inner_type) = extract_inner_type(field_type, "Vec") {
+        if !is_single_var(inner_type) {
+            return generate_dispatch_expr(inner_type, quote!(vec_vars(source, #env_var_name, true)));
+        }
+        return quote! { vec![(#env_var_name.to_string(), true)] };
+    }
+
+    if is_single_var(field_type) {
+        return quote! { vec![(#env_var_name.to_string(), true)] };
+    }
+
+    if extract_map_types(field_type).is_some() {
+        return quote! { from_env::__private::map_vars(source, #env_var_name, nesting_separator, field_vars, true) };
+    }
+
+    generate_dispatch_expr(field_type, quote!(vars(source, #env_var_name, true)))
+}
+
+/// Parses an `i32` field tagged `#[prost(enumeration = "...")]` from either a
+/// number or a variant name, via the enum's prost-generated `from_str_name`.
+/// Handles `i32`, `Option<i32>` and `Vec<i32>`.
+fn generate_enumeration_parse_expr(
+    field_type: &syn::Type,
+    enum_path: &syn::Path,
+    env_var_name: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let enum_name = enum_path.segments.last().unwrap().ident.to_string();
+    let variant_prefix = format!("{}_", to_env_case(&enum_name, Some("_")));
+    let value_parser = quote! {
+        |value: &str| from_env::__private::parse_prost_enum::<#enum_path>(
+            value,
+            #enum_name,
+            #variant_prefix,
+            #enum_path::from_str_name,
+            #enum_path::as_str_name,
+        )
+    };
+
+    let required = |parse: proc_macro2::TokenStream| {
+        quote! {
+            {
+                let value = source.get(#env_var_name)
+                    .ok_or_else(|| from_env::FromEnvError::MissingVariable(#env_var_name.to_string()))?;
+                (#parse)(&value)
+                    .map_err(|e| from_env::FromEnvError::ParseError {
+                        var: #env_var_name.to_string(),
+                        source: e,
+                    })?
+            }
+        }
+    };
+
+    if extract_inner_type(field_type, "Option").is_some() {
+        let inner_parse = required(value_parser);
+        return quote! {
+            match source.get(#env_var_name) {
+                Some(_) => Some(#inner_parse),
+                None => None,
+            }
+        };
+    }
+
+    if extract_inner_type(field_type, "Vec").is_some() {
+        return required(quote! {
+            |value: &str| from_env::parse_vec_with(value, #value_parser)
+        });
+    }
+
+    required(value_parser)
+}
+
+/// The type's last path segment when it has no generic arguments, so
+/// `::prost::alloc::string::String` reads as `String` the way prost-build
+/// spells it. Anything else is the whole type without spaces.
+fn simple_type_name(ty: &syn::Type) -> String {
+    if let syn::Type::Path(type_path) = ty {
+        if let Some(segment) = type_path.path.segments.last() {
+            if type_path.qself.is_none() && segment.arguments.is_empty() {
+                return segment.ident.to_string();
+            }
+        }
+    }
+    quote!(#ty).to_string().replace(" ", "")
+}
+
+fn is_primitive(ty: &syn::Type) -> bool {
+    let type_str = simple_type_name(ty);
+    matches!(
+        type_str.as_str(),
+        "String" | "bool" | "char" |
+        "i8" | "i16" | "i32" | "i64" | "i128" | "isize" |
+        "u8" | "u16" | "u32" | "u64" | "u128" | "usize" |
+        "f32" | "f64"
+    )
+}
+
+/// `Vec<u8>` or a `Bytes` type, what prost emits for proto `bytes`. prost
+/// spells the former `::prost::alloc::vec::Vec<u8>`.
+fn is_bytes(ty: &syn::Type) -> bool {
+    if extract_inner_type(ty, "Vec").is_some_and(|inner| quote!(#inner).to_string() == "u8") {
+        return true;
+    }
+    if let syn::Type::Path(type_path) = ty {
+        if let Some(segment) = type_path.path.segments.last() {
+            return segment.ident == "Bytes" && segment.arguments.is_empty();
+        }
+    }
+    false
+}
+
+/// Types read from exactly one variable without going through `Field`.
+fn is_single_var(ty: &syn::Type) -> bool {
+    is_primitive(ty) || is_bytes(ty)
+}
+
+/// Decodes a bytes field, `Option` of one, or `Vec` of them (comma-separated).
+/// `None` if `field_type` isn't one of those.
+fn generate_bytes_parse_expr(
+    field_type: &syn::Type,
+    encoding: &str,
+    env_var_name: &proc_macro2::TokenStream,
+) -> Option<proc_macro2::TokenStream> {
+    let encoding = match encoding {
+        "base64" => quote!(from_env::BytesEncoding::Base64),
+        "base64url" => quote!(from_env::BytesEncoding::Base64Url),
+        "hex" => quote!(from_env::BytesEncoding::Hex),
+        "raw" => quote!(from_env::BytesEncoding::Raw),
+        other => panic!("Unknown encoding '{}', expected base64, base64url, hex or raw", other),
+    };
+
+    let required = |bytes_type: &syn::Type, parse: proc_macro2::TokenStream| {
+        quote! {
+            {
+                let value = source.get(#env_var_name)
+                    .ok_or_else(|| from_env::FromEnvError::MissingVariable(#env_var_name.to_string()))?;
+                (#parse)(&value)
+                    .map(<#bytes_type>::from)
+                    .map_err(|e| from_env::FromEnvError::ParseError {
+                        var: #env_var_name.to_string(),
+                        source: e,
+                    })?
+            }
+        }
+    };
+    let decode = quote! { |value: &str| from_env::parse_bytes(value, #encoding) };
+
+    if is_bytes(field_type) {
+        return Some(required(field_type, decode));
+    }
+
+    if let Some(inner_type) = extract_inner_type(field_type, "Option") {
+        if is_bytes(inner_type) {
+            let inner_parse = required(inner_type, decode);
+            return Some(quote! {
+                match source.get(#env_var_name) {
+                    Some(_) => Some(#inner_parse),
+                    None => None,
+                }
+            });
+        }
+    }
+
+    if let Some(inner_type) = extract_inner_type(field_type, "Vec") {
+        if is_bytes(inner_type) {
+            return Some(required(
+                field_type,
+                quote! {
+                    |value: &str| from_env::parse_vec_with(value, |item| (#decode)(item).map(<#inner_type>::from))
+                },
+            ));
+        }
+    }
+
+    None
+}
+
+/// Loads a non-primitive field through `from_env::__private::Field`, which
+/// picks the nested `FromEnvTrait` impl when there is one and `FromStr`
+/// otherwise. `call` is the method call on it, e.g. `required(source, env_var_name)`.
+fn generate_dispatch_expr(field_type: &syn::Type, call: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let type_name = quote!(#field_type).to_string().replace(' ', "");
+    quote! {
+        {
+            #[allow(unused_imports)]
+            use from_env::__private::{ViaFromEnv as _, ViaFromStr as _};
+            (&from_env::__private::Field::<#field_type>::new(nesting_separator, #type_name)).#call
+        }
+    }
+}
+
+fn generate_vec_parse_expr(inner_type: &syn::Type, env_var_name: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let type_str = simple_type_name(inner_type);
 
     match type_str.as_str() {
         "String" => {
             quote! {
                 {
-                    let value = std::env::var(#env_var_name)
-                        .map_err(|_| from_env::FromEnvError::MissingVariable(#env_var_name.to_string()))?;
+                    let value = source.get(#env_var_name)
+                        .ok_or_else(|| from_env::FromEnvError::MissingVariable(#env_var_name.to_string()))?;
                     from_env::parse_vec_string(&value)
                         .map_err(|e| from_env::FromEnvError::ParseError {
                             var: #env_var_name.to_string(),
@@ -262,8 +1115,8 @@ fn generate_vec_parse_expr(inner_type: &syn::Type, env_var_name: &str) -> proc_m
         "bool" => {
             quote! {
                 {
-                    let value = std::env::var(#env_var_name)
-                        .map_err(|_| from_env::FromEnvError::MissingVariable(#env_var_name.to_string()))?;
+                    let value = source.get(#env_var_name)
+                        .ok_or_else(|| from_env::FromEnvError::MissingVariable(#env_var_name.to_string()))?;
                     from_env::parse_vec_bool(&value)
                         .map_err(|e| from_env::FromEnvError::ParseError {
                             var: #env_var_name.to_string(),
@@ -275,8 +1128,8 @@ fn generate_vec_parse_expr(inner_type: &syn::Type, env_var_name: &str) -> proc_m
         "char" => {
             quote! {
                 {
-                    let value = std::env::var(#env_var_name)
-                        .map_err(|_| from_env::FromEnvError::MissingVariable(#env_var_name.to_string()))?;
+                    let value = source.get(#env_var_name)
+                        .ok_or_else(|| from_env::FromEnvError::MissingVariable(#env_var_name.to_string()))?;
                     from_env::parse_vec_char(&value)
                         .map_err(|e| from_env::FromEnvError::ParseError {
                             var: #env_var_name.to_string(),
@@ -289,8 +1142,8 @@ fn generate_vec_parse_expr(inner_type: &syn::Type, env_var_name: &str) -> proc_m
         "u8" | "u16" | "u32" | "u64" | "u128" | "usize" => {
             quote! {
                 {
-                    let value = std::env::var(#env_var_name)
-                        .map_err(|_| from_env::FromEnvError::MissingVariable(#env_var_name.to_string()))?;
+                    let value = source.get(#env_var_name)
+                        .ok_or_else(|| from_env::FromEnvError::MissingVariable(#env_var_name.to_string()))?;
                     from_env::parse_vec_int::<#inner_type>(&value)
                         .map_err(|e| from_env::FromEnvError::ParseError {
                             var: #env_var_name.to_string(),
@@ -302,8 +1155,8 @@ fn generate_vec_parse_expr(inner_type: &syn::Type, env_var_name: &str) -> proc_m
         "f32" | "f64" => {
             quote! {
                 {
-                    let value = std::env::var(#env_var_name)
-                        .map_err(|_| from_env::FromEnvError::MissingVariable(#env_var_name.to_string()))?;
+                    let value = source.get(#env_var_name)
+                        .ok_or_else(|| from_env::FromEnvError::MissingVariable(#env_var_name.to_string()))?;
                     from_env::parse_vec_float::<#inner_type>(&value)
                         .map_err(|e| from_env::FromEnvError::ParseError {
                             var: #env_var_name.to_string(),
@@ -313,18 +1166,10 @@ fn generate_vec_parse_expr(inner_type: &syn::Type, env_var_name: &str) -> proc_m
             }
         }
         _ => {
-            // For any other type, try generic vector parsing
-            quote! {
-                {
-                    let value = std::env::var(#env_var_name)
-                        .map_err(|_| from_env::FromEnvError::MissingVariable(#env_var_name.to_string()))?;
-                    from_env::parse_vec_int::<#inner_type>(&value)
-                        .map_err(|e| from_env::FromEnvError::ParseError {
-                            var: #env_var_name.to_string(),
-                            source: e,
-                        })?
-                }
-            }
+            // Any other element type goes through `Field`: indexed for nested
+            // structs, comma-separated via `FromStr` otherwise
+            let dispatch = generate_dispatch_expr(inner_type, quote!(vec(source, #env_var_name)));
+            quote! { #dispatch? }
         }
     }
 }
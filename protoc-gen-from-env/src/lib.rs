@@ -0,0 +1,157 @@
+//! A protoc plugin for `from-env`.
+//!
+//! For each `.proto` file it is asked to generate, it writes two files:
+//!
+//! - `<name>.from_env.rs`, a `configure(&mut prost_build::Config)` function
+//!   adding `#[derive(from_env::FromEnv)]` (and the plugin options as
+//!   `#[from_env(...)]`) to every message and oneof in the file
+//! - `<name>.env.md`, a reference of the environment variable each field is
+//!   read from
+//!
+//! Options are passed as `--from-env_opt=prefix=APP_,word_separator=_`, or
+//! `rename_all=SCREAMING_SNAKE` in place of `word_separator`, and
+//! `nesting_separator=__` to join path segments with something other than `_`.
+
+mod naming;
+mod reference;
+mod wiring;
+
+use naming::RenameRule;
+use prost_types::compiler::{code_generator_response, CodeGeneratorRequest, CodeGeneratorResponse};
+use prost_types::{DescriptorProto, FileDescriptorProto};
+use std::collections::HashMap;
+
+/// Plugin options, the same ones `#[from_env(...)]` takes on a struct.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Options {
+    pub prefix: Option<String>,
+    pub word_separator: Option<String>,
+    pub rename_all: Option<RenameRule>,
+    pub nesting_separator: Option<String>,
+}
+
+impl Options {
+    /// Parses protoc's `key=value,key=value` parameter string.
+    pub fn parse(parameter: &str) -> Result<Self, String> {
+        let mut options = Options::default();
+        for option in parameter.split(',').map(str::trim).filter(|o| !o.is_empty()) {
+            match option.split_once('=') {
+                Some(("prefix", value)) => options.prefix = Some(value.to_string()),
+                Some(("word_separator", value)) => options.word_separator = Some(value.to_string()),
+                Some(("nesting_separator", value)) => options.nesting_separator = Some(value.to_string()),
+                Some(("rename_all", value)) => {
+                    options.rename_all = Some(
+                        RenameRule::parse(value).ok_or_else(|| format!("unknown rename_all rule '{}'", value))?,
+                    );
+                }
+                _ => {
+                    return Err(format!(
+                        "unknown option '{}', expected prefix=..., word_separator=..., rename_all=... or nesting_separator=...",
+                        option
+                    ))
+                }
+            }
+        }
+        if options.rename_all.is_some() && options.word_separator.is_some() {
+            return Err("rename_all and word_separator can't be used together".to_string());
+        }
+        Ok(options)
+    }
+
+    /// What joins path segments: the one set, else the `rename_all` rule's, else `_`.
+    pub(crate) fn nesting_separator(&self) -> &str {
+        self.nesting_separator
+            .as_deref()
+            .unwrap_or_else(|| self.rename_all.map_or("_", RenameRule::nesting_separator))
+    }
+
+    /// The `#[from_env(...)]` attribute these options stand for, if any.
+    fn attribute(&self) -> Option<String> {
+        let mut args = Vec::new();
+        if let Some(prefix) = &self.prefix {
+            args.push(format!("prefix = {:?}", prefix));
+        }
+        if let Some(separator) = &self.word_separator {
+            args.push(format!("word_separator = {:?}", separator));
+        }
+        if let Some(rule) = self.rename_all {
+            args.push(format!("rename_all = {:?}", rule.as_str()));
+        }
+        if let Some(separator) = &self.nesting_separator {
+            args.push(format!("nesting_separator = {:?}", separator));
+        }
+        (!args.is_empty()).then(|| format!("#[from_env({})]", args.join(", ")))
+    }
+}
+
+/// Every message in the request, by fully qualified name (`.example.AppConfig`).
+pub(crate) struct Messages<'a> {
+    by_name: HashMap<String, &'a DescriptorProto>,
+}
+
+impl<'a> Messages<'a> {
+    fn new(files: &'a [FileDescriptorProto]) -> Self {
+        fn add<'a>(by_name: &mut HashMap<String, &'a DescriptorProto>, scope: &str, message: &'a DescriptorProto) {
+            let name = format!("{}.{}", scope, message.name());
+            for nested in &message.nested_type {
+                add(by_name, &name, nested);
+            }
+            by_name.insert(name, message);
+        }
+
+        let mut by_name = HashMap::new();
+        for file in files {
+            let scope = match file.package() {
+                "" => String::new(),
+                package => format!(".{}", package),
+            };
+            for message in &file.message_type {
+                add(&mut by_name, &scope, message);
+            }
+        }
+        Messages { by_name }
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<&'a DescriptorProto> {
+        self.by_name.get(name).copied()
+    }
+}
+
+/// Runs the plugin. Problems are reported through the response's `error`,
+/// as protoc expects.
+pub fn generate(request: &CodeGeneratorRequest) -> CodeGeneratorResponse {
+    let mut response = CodeGeneratorResponse {
+        supported_features: Some(code_generator_response::Feature::Proto3Optional as u64),
+        ..Default::default()
+    };
+
+    let options = match Options::parse(request.parameter()) {
+        Ok(options) => options,
+        Err(e) => {
+            response.error = Some(e);
+            return response;
+        }
+    };
+    let messages = Messages::new(&request.proto_file);
+
+    for file_name in &request.file_to_generate {
+        let Some(file) = request.proto_file.iter().find(|f| f.name() == file_name) else {
+            response.error = Some(format!("{} is not in the request", file_name));
+            return response;
+        };
+        let stem = file_name.strip_suffix(".proto").unwrap_or(file_name);
+
+        response.file.push(code_generator_response::File {
+            name: Some(format!("{}.from_env.rs", stem)),
+            content: Some(wiring::generate(file, &options)),
+            ..Default::default()
+        });
+        response.file.push(code_generator_response::File {
+            name: Some(format!("{}.env.md", stem)),
+            content: Some(reference::generate(file, &messages, &options)),
+            ..Default::default()
+        });
+    }
+
+    response
+}
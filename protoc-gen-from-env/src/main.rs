@@ -0,0 +1,14 @@
+use prost::Message;
+use prost_types::compiler::CodeGeneratorRequest;
+use std::io::{Read, Write};
+
+fn main() -> std::io::Result<()> {
+    let mut input = Vec::new();
+    std::io::stdin().read_to_end(&mut input)?;
+
+    let request = CodeGeneratorRequest::decode(input.as_slice())
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    let response = protoc_gen_from_env::generate(&request);
+
+    std::io::stdout().write_all(&response.encode_to_vec())
+}
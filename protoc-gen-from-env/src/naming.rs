@@ -0,0 +1,12 @@
+pub use from_env_case::{to_env_case, RenameRule};
+use heck::{ToSnakeCase, ToUpperCamelCase};
+
+/// The struct, enum or oneof variant name prost generates for a proto name.
+pub fn rust_type_name(proto_name: &str) -> String {
+    proto_name.to_upper_camel_case()
+}
+
+/// The field name prost generates for a proto field.
+pub fn rust_field_name(proto_name: &str) -> String {
+    proto_name.to_snake_case()
+}
@@ -0,0 +1,257 @@
+use crate::naming::{rust_field_name, rust_type_name, to_env_case};
+use crate::{Messages, Options};
+use prost_types::field_descriptor_proto::{Label, Type};
+use prost_types::{DescriptorProto, FieldDescriptorProto, FileDescriptorProto};
+
+/// One line of the reference: a variable, the field path it sets and its type.
+struct Row {
+    variable: String,
+    field: String,
+    ty: String,
+}
+
+/// A Markdown reference of every variable the messages in `file` read.
+pub fn generate(file: &FileDescriptorProto, messages: &Messages<'_>, options: &Options) -> String {
+    let scope = match file.package() {
+        "" => String::new(),
+        package => format!(".{}", package),
+    };
+
+    let mut out = format!("# Environment variables for `{}`\n", file.name());
+    for message in &file.message_type {
+        section(message, &scope, messages, options, &mut out);
+    }
+    out
+}
+
+fn section(message: &DescriptorProto, scope: &str, messages: &Messages<'_>, options: &Options, out: &mut String) {
+    if message.options.as_ref().is_some_and(|o| o.map_entry()) {
+        return;
+    }
+
+    let full_name = format!("{}.{}", scope, message.name());
+    let struct_name = rust_type_name(message.name());
+    let struct_prefix = format!(
+        "{}{}{}",
+        options.prefix.as_deref().unwrap_or(""),
+        match options.rename_all {
+            Some(rule) => rule.apply(&struct_name),
+            None => to_env_case(&struct_name, options.word_separator.as_deref()),
+        },
+        options.nesting_separator()
+    );
+
+    let mut rows = Vec::new();
+    let mut stack = vec![full_name.clone()];
+    fields(message, &struct_prefix, "", messages, options, &mut stack, &mut rows);
+
+    out.push_str(&format!("\n## `{}`\n\n", full_name.trim_start_matches('.')));
+    out.push_str("| Variable | Field | Type |\n| --- | --- | --- |\n");
+    for row in rows {
+        out.push_str(&format!("| `{}` | `{}` | {} |\n", row.variable, row.field, row.ty));
+    }
+
+    for nested in &message.nested_type {
+        section(nested, &full_name, messages, options, out);
+    }
+}
+
+/// Adds the rows for `message`'s fields, read under `prefix`. `stack` holds
+/// the messages being expanded, to stop at recursive ones.
+fn fields(
+    message: &DescriptorProto,
+    prefix: &str,
+    path: &str,
+    messages: &Messages<'_>,
+    options: &Options,
+    stack: &mut Vec<String>,
+    rows: &mut Vec<Row>,
+) {
+    let mut seen_oneofs = Vec::new();
+
+    for field in &message.field {
+        let field_path = format!("{}{}", path, field.name());
+
+        if let Some(index) = field.oneof_index.filter(|_| !field.proto3_optional()) {
+            if seen_oneofs.contains(&index) {
+                continue;
+            }
+            seen_oneofs.push(index);
+            oneof(message, index, prefix, path, messages, options, stack, rows);
+            continue;
+        }
+
+        let variable = format!("{}{}", prefix, field_segment(field.name(), options));
+        value(field, variable, field_path, messages, options, stack, rows);
+    }
+}
+
+/// The discriminator of oneof `index`, then each case's payload under it.
+#[allow(clippy::too_many_arguments)]
+fn oneof(
+    message: &DescriptorProto,
+    index: i32,
+    prefix: &str,
+    path: &str,
+    messages: &Messages<'_>,
+    options: &Options,
+    stack: &mut Vec<String>,
+    rows: &mut Vec<Row>,
+) {
+    let name = message.oneof_decl[index as usize].name();
+    let discriminator = format!("{}{}", prefix, field_segment(name, options));
+    let cases: Vec<&FieldDescriptorProto> = message
+        .field
+        .iter()
+        .filter(|f| f.oneof_index == Some(index))
+        .collect();
+
+    rows.push(Row {
+        variable: discriminator.clone(),
+        field: format!("{}{}", path, name),
+        ty: format!(
+            "oneof: {}",
+            cases
+                .iter()
+                .map(|f| format!("`{}`", f.name()))
+                .collect::<Vec<_>>()
+                .join(" \\| ")
+        ),
+    });
+
+    for case in cases {
+        let variable = format!(
+            "{}{}{}",
+            discriminator,
+            options.nesting_separator(),
+            to_env_case(&rust_type_name(case.name()), Some("_"))
+        );
+        value(case, variable, format!("{}{}", path, case.name()), messages, options, stack, rows);
+    }
+}
+
+/// Adds the rows for one field read from `variable`.
+fn value(
+    field: &FieldDescriptorProto,
+    variable: String,
+    field_path: String,
+    messages: &Messages<'_>,
+    options: &Options,
+    stack: &mut Vec<String>,
+    rows: &mut Vec<Row>,
+) {
+    let repeated = field.label() == Label::Repeated;
+    let separator = options.nesting_separator();
+    let nested = (field.r#type() == Type::Message)
+        .then(|| messages.get(field.type_name()))
+        .flatten();
+
+    match nested {
+        Some(entry) if entry.options.as_ref().is_some_and(|o| o.map_entry()) => {
+            let ty = format!(
+                "`map<{}, {}>`",
+                type_name(&entry.field[0]),
+                type_name(&entry.field[1])
+            );
+            rows.push(Row {
+                variable: variable.clone(),
+                field: field_path.clone(),
+                ty: format!("{} as `key=value,...`", ty),
+            });
+            rows.push(Row {
+                variable: format!("{}{}<KEY>", variable, separator),
+                field: field_path,
+                ty: format!("{} entry", ty),
+            });
+        }
+        Some(nested) if !is_single_var(field.type_name()) => {
+            if stack.iter().any(|name| name == field.type_name()) {
+                rows.push(Row {
+                    variable: format!("{}{}*", variable, separator),
+                    field: field_path,
+                    ty: format!("`{}` (recursive, not expanded)", type_label(field.type_name())),
+                });
+                return;
+            }
+
+            stack.push(field.type_name().to_string());
+            if repeated {
+                rows.push(Row {
+                    variable: format!("{}{}COUNT", variable, separator),
+                    field: field_path.clone(),
+                    ty: "optional length of the list".to_string(),
+                });
+                let prefix = format!("{}{}<N>{}", variable, separator, separator);
+                fields(nested, &prefix, &format!("{}[N].", field_path), messages, options, stack, rows);
+            } else {
+                let prefix = format!("{}{}", variable, separator);
+                fields(nested, &prefix, &format!("{}.", field_path), messages, options, stack, rows);
+            }
+            stack.pop();
+        }
+        _ => rows.push(Row {
+            variable,
+            field: field_path,
+            ty: if repeated {
+                format!("repeated {}, comma-separated", type_name(field))
+            } else {
+                type_name(field)
+            },
+        }),
+    }
+}
+
+/// A field's segment of its variable name, as the derive spells it.
+fn field_segment(proto_name: &str, options: &Options) -> String {
+    let name = rust_field_name(proto_name);
+    match options.rename_all {
+        Some(rule) => rule.apply(&name),
+        None => name.to_uppercase(),
+    }
+}
+
+/// Well-known messages that are read from a single variable rather than
+/// expanded: `Duration`, `Timestamp` (with the `prost-types` feature) and the
+/// `*Value` wrappers, which prost renders as `Option<T>`.
+fn is_single_var(type_name: &str) -> bool {
+    matches!(
+        type_name,
+        ".google.protobuf.Duration"
+            | ".google.protobuf.Timestamp"
+            | ".google.protobuf.DoubleValue"
+            | ".google.protobuf.FloatValue"
+            | ".google.protobuf.Int64Value"
+            | ".google.protobuf.UInt64Value"
+            | ".google.protobuf.Int32Value"
+            | ".google.protobuf.UInt32Value"
+            | ".google.protobuf.BoolValue"
+            | ".google.protobuf.StringValue"
+            | ".google.protobuf.BytesValue"
+    )
+}
+
+fn type_label(type_name: &str) -> &str {
+    type_name.trim_start_matches('.')
+}
+
+fn type_name(field: &FieldDescriptorProto) -> String {
+    match field.r#type() {
+        Type::Double => "double".to_string(),
+        Type::Float => "float".to_string(),
+        Type::Int64 => "int64".to_string(),
+        Type::Uint64 => "uint64".to_string(),
+        Type::Int32 => "int32".to_string(),
+        Type::Fixed64 => "fixed64".to_string(),
+        Type::Fixed32 => "fixed32".to_string(),
+        Type::Bool => "bool".to_string(),
+        Type::String => "string".to_string(),
+        Type::Bytes => "bytes, base64".to_string(),
+        Type::Uint32 => "uint32".to_string(),
+        Type::Sfixed32 => "sfixed32".to_string(),
+        Type::Sfixed64 => "sfixed64".to_string(),
+        Type::Sint32 => "sint32".to_string(),
+        Type::Sint64 => "sint64".to_string(),
+        Type::Enum => format!("`{}` by name or number", type_label(field.type_name())),
+        Type::Message | Type::Group => format!("`{}`", type_label(field.type_name())),
+    }
+}
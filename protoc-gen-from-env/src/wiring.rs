@@ -0,0 +1,42 @@
+use crate::Options;
+use prost_types::FileDescriptorProto;
+
+const DERIVE: &str = "#[derive(from_env::FromEnv)]";
+
+/// The `configure` function adding the derive to every message, enum and
+/// oneof in `file`.
+///
+/// prost-build matches a path against everything underneath it too, so each
+/// attribute goes on the file's top-level types only; nested messages, enums
+/// and oneofs pick it up from their parent. Adding it to them as well would
+/// derive `FromEnv` twice.
+pub fn generate(file: &FileDescriptorProto, options: &Options) -> String {
+    let scope = match file.package() {
+        "" => String::new(),
+        package => format!(".{}", package),
+    };
+    let attribute = options.attribute();
+
+    let mut out = format!(
+        "// @generated by protoc-gen-from-env from {}. Do not edit.\n\n\
+         /// Adds `FromEnv` to the messages in `{}`.\n\
+         pub fn configure(config: &mut prost_build::Config) -> &mut prost_build::Config {{\n    config",
+        file.name(),
+        file.name(),
+    );
+    for message in &file.message_type {
+        let path = format!("{}.{}", scope, message.name());
+        out.push_str(&format!("\n        .type_attribute({:?}, {:?})", path, DERIVE));
+        // Only messages take the struct options, not the enums under them
+        if let Some(attribute) = &attribute {
+            out.push_str(&format!("\n        .message_attribute({:?}, {:?})", path, attribute));
+        }
+    }
+    // Proto enums list their variants for `#[prost(enumeration = "...")]` fields
+    for enumeration in &file.enum_type {
+        let path = format!("{}.{}", scope, enumeration.name());
+        out.push_str(&format!("\n        .type_attribute({:?}, {:?})", path, DERIVE));
+    }
+    out.push_str("\n}\n");
+    out
+}
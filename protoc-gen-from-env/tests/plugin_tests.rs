@@ -0,0 +1,138 @@
+//! Runs the plugin against `data/config.bin`, a `FileDescriptorSet` compiled
+//! from `examples/config.proto`. Regenerate it after changing the example:
+//!
+//! ```text
+//! protoc --include_imports --descriptor_set_out=protoc-gen-from-env/tests/data/config.bin \
+//!        -I examples examples/config.proto
+//! ```
+
+use prost::Message;
+use prost_types::compiler::{CodeGeneratorRequest, CodeGeneratorResponse};
+use prost_types::FileDescriptorSet;
+
+fn run(parameter: &str) -> CodeGeneratorResponse {
+    let descriptors = FileDescriptorSet::decode(&include_bytes!("data/config.bin")[..]).unwrap();
+    let request = CodeGeneratorRequest {
+        file_to_generate: vec!["config.proto".to_string()],
+        parameter: Some(parameter.to_string()),
+        proto_file: descriptors.file,
+        ..Default::default()
+    };
+    protoc_gen_from_env::generate(&request)
+}
+
+fn output<'a>(response: &'a CodeGeneratorResponse, name: &str) -> &'a str {
+    response
+        .file
+        .iter()
+        .find(|f| f.name() == name)
+        .unwrap_or_else(|| panic!("{} was not generated", name))
+        .content()
+}
+
+#[test]
+fn test_wiring() {
+    let response = run("prefix=APP_,word_separator=_");
+    assert_eq!(response.error, None);
+    assert_eq!(
+        output(&response, "config.from_env.rs"),
+        r##"// @generated by protoc-gen-from-env from config.proto. Do not edit.
+
+/// Adds `FromEnv` to the messages in `config.proto`.
+pub fn configure(config: &mut prost_build::Config) -> &mut prost_build::Config {
+    config
+        .type_attribute(".example.DatabaseConfig", "#[derive(from_env::FromEnv)]")
+        .message_attribute(".example.DatabaseConfig", "#[from_env(prefix = \"APP_\", word_separator = \"_\")]")
+        .type_attribute(".example.ServerConfig", "#[derive(from_env::FromEnv)]")
+        .message_attribute(".example.ServerConfig", "#[from_env(prefix = \"APP_\", word_separator = \"_\")]")
+        .type_attribute(".example.AppConfig", "#[derive(from_env::FromEnv)]")
+        .message_attribute(".example.AppConfig", "#[from_env(prefix = \"APP_\", word_separator = \"_\")]")
+        .type_attribute(".example.FeatureFlags", "#[derive(from_env::FromEnv)]")
+        .message_attribute(".example.FeatureFlags", "#[from_env(prefix = \"APP_\", word_separator = \"_\")]")
+        .type_attribute(".example.LogLevel", "#[derive(from_env::FromEnv)]")
+}
+"##
+    );
+
+    // Without options only the derive is added
+    let response = run("");
+    let wiring = output(&response, "config.from_env.rs");
+    assert!(wiring.contains(r##".type_attribute(".example.DatabaseConfig", "#[derive(from_env::FromEnv)]")"##));
+    assert!(!wiring.contains("message_attribute"));
+}
+
+#[test]
+fn test_reference() {
+    let response = run("prefix=APP_,word_separator=_");
+    let reference = output(&response, "config.env.md");
+    for row in [
+        "| `APP_DATABASE_CONFIG_HOST` | `host` | string |",
+        "| `APP_APP_CONFIG_DATABASE_SSL_ENABLED` | `database.ssl_enabled` | bool |",
+        "| `APP_APP_CONFIG_LOG_LEVEL` | `log_level` | `example.LogLevel` by name or number |",
+        "| `APP_APP_CONFIG_LABELS` | `labels` | `map<string, string>` as `key=value,...` |",
+        "| `APP_APP_CONFIG_LABELS_<KEY>` | `labels` | `map<string, string>` entry |",
+        "| `APP_APP_CONFIG_REPLICAS_COUNT` | `replicas` | optional length of the list |",
+        "| `APP_APP_CONFIG_REPLICAS_<N>_PORT` | `replicas[N].port` | uint32 |",
+        "| `APP_APP_CONFIG_CACHE` | `cache` | oneof: `redis_url` \\| `memcached_url` |",
+        "| `APP_APP_CONFIG_CACHE_REDIS_URL` | `redis_url` | string |",
+    ] {
+        assert!(reference.contains(row), "missing row {}\n{}", row, reference);
+    }
+
+    // Names follow the derive's defaults without options
+    let response = run("");
+    let reference = output(&response, "config.env.md");
+    assert!(reference.contains("| `DATABASECONFIG_HOST` | `host` | string |"));
+    assert!(reference.contains("| `APPCONFIG_SERVER_DEBUG_MODE` | `server.debug_mode` | bool |"));
+}
+
+#[test]
+fn test_rename_all() {
+    let response = run("rename_all=kebab");
+    assert_eq!(response.error, None);
+    assert!(output(&response, "config.from_env.rs")
+        .contains(r##".message_attribute(".example.AppConfig", "#[from_env(rename_all = \"kebab\")]")"##));
+
+    let reference = output(&response, "config.env.md");
+    assert!(reference.contains("| `database-config-host` | `host` | string |"));
+    assert!(reference.contains("| `app-config-server-debug-mode` | `server.debug_mode` | bool |"));
+
+    let response = run("rename_all=Title");
+    assert_eq!(response.error.as_deref(), Some("unknown rename_all rule 'Title'"));
+
+    let response = run("rename_all=snake,word_separator=_");
+    assert_eq!(
+        response.error.as_deref(),
+        Some("rename_all and word_separator can't be used together")
+    );
+}
+
+#[test]
+fn test_nesting_separator() {
+    let response = run("prefix=APP__,rename_all=SCREAMING_SNAKE,nesting_separator=__");
+    assert_eq!(response.error, None);
+    assert!(output(&response, "config.from_env.rs").contains(
+        r##"#[from_env(prefix = \"APP__\", rename_all = \"SCREAMING_SNAKE\", nesting_separator = \"__\")]"##
+    ));
+
+    let reference = output(&response, "config.env.md");
+    for row in [
+        "| `APP__APP_CONFIG__DATABASE__SSL_ENABLED` | `database.ssl_enabled` | bool |",
+        "| `APP__APP_CONFIG__LABELS__<KEY>` | `labels` | `map<string, string>` entry |",
+        "| `APP__APP_CONFIG__REPLICAS__COUNT` | `replicas` | optional length of the list |",
+        "| `APP__APP_CONFIG__REPLICAS__<N>__PORT` | `replicas[N].port` | uint32 |",
+        "| `APP__APP_CONFIG__CACHE__REDIS_URL` | `redis_url` | string |",
+    ] {
+        assert!(reference.contains(row), "missing row {}\n{}", row, reference);
+    }
+}
+
+#[test]
+fn test_unknown_option() {
+    let response = run("prefix=APP_,suffix=_X");
+    assert_eq!(
+        response.error.as_deref(),
+        Some("unknown option 'suffix=_X', expected prefix=..., word_separator=..., rename_all=... or nesting_separator=...")
+    );
+    assert!(response.file.is_empty());
+}
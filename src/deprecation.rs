@@ -0,0 +1,49 @@
+use std::sync::RwLock;
+
+/// A field was loaded from one of its `#[from_env(deprecated = "...")]` names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deprecation<'a> {
+    /// The deprecated variable that was read.
+    pub name: &'a str,
+    /// The variable that should be set instead.
+    pub replacement: &'a str,
+}
+
+impl std::fmt::Display for Deprecation<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Environment variable '{}' is deprecated, use '{}' instead",
+            self.name, self.replacement
+        )
+    }
+}
+
+type Hook = Box<dyn Fn(&Deprecation<'_>) + Send + Sync>;
+
+static HOOK: RwLock<Option<Hook>> = RwLock::new(None);
+
+/// Sets the function called whenever a deprecated variable name is used,
+/// replacing any previous hook.
+///
+/// Without a hook, deprecations are logged with `log::warn!` when the `log`
+/// feature is enabled, and ignored otherwise.
+pub fn set_deprecation_hook(hook: impl Fn(&Deprecation<'_>) + Send + Sync + 'static) {
+    *HOOK.write().unwrap_or_else(|e| e.into_inner()) = Some(Box::new(hook));
+}
+
+pub(crate) fn report(deprecation: &Deprecation<'_>) {
+    let hook = HOOK.read().unwrap_or_else(|e| e.into_inner());
+    match hook.as_ref() {
+        Some(hook) => hook(deprecation),
+        None => report_without_hook(deprecation),
+    }
+}
+
+#[cfg(feature = "log")]
+fn report_without_hook(deprecation: &Deprecation<'_>) {
+    log::warn!("{}", deprecation);
+}
+
+#[cfg(not(feature = "log"))]
+fn report_without_hook(_deprecation: &Deprecation<'_>) {}
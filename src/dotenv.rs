@@ -0,0 +1,318 @@
+use crate::EnvSource;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum DotEnvError {
+    #[error("Failed to read '{}': {source}", .path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("{}:{line}:{column}: {message}", display_path(.path.as_deref()))]
+    Syntax {
+        path: Option<PathBuf>,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+}
+
+fn display_path(path: Option<&Path>) -> String {
+    match path {
+        Some(path) => path.display().to_string(),
+        None => "<string>".to_string(),
+    }
+}
+
+/// Variables parsed from a `.env` file.
+///
+/// Supports the usual dotenv syntax:
+///
+/// ```text
+/// # comments, on their own line or after an unquoted value
+/// export HOST=localhost          # optional `export` prefix
+/// GREETING="hello\n\"world\""    # double quotes: \n \r \t \\ \" \$ escapes
+/// PATTERN='no \escapes here'     # single quotes: taken literally
+/// CERT="-----BEGIN CERT-----
+/// MIIB...
+/// -----END CERT-----"            # quoted values may span lines
+/// ```
+///
+/// When a key appears more than once the last definition wins. Each value
+/// remembers the line it was defined on, so parse errors for file-sourced
+/// values point back at the file.
+#[derive(Debug, Clone, Default)]
+pub struct DotEnv {
+    path: Option<PathBuf>,
+    vars: BTreeMap<String, (String, usize)>,
+}
+
+impl DotEnv {
+    /// Reads and parses the file at `path`.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, DotEnvError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|source| DotEnvError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Parser::new(&content, Some(path)).parse()
+    }
+
+    /// Parses dotenv-formatted `content` that didn't come from a file.
+    pub fn parse(content: &str) -> Result<Self, DotEnvError> {
+        Parser::new(content, None).parse()
+    }
+
+    /// The file this was read from, if any.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.vars.get(name).map(|(value, _)| value.as_str())
+    }
+
+    /// The 1-based line `name` was defined on.
+    pub fn line(&self, name: &str) -> Option<usize> {
+        self.vars.get(name).map(|(_, line)| *line)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.vars
+            .iter()
+            .map(|(name, (value, _))| (name.as_str(), value.as_str()))
+    }
+}
+
+impl EnvSource for DotEnv {
+    fn get(&self, name: &str) -> Option<String> {
+        DotEnv::get(self, name).map(str::to_string)
+    }
+
+    fn vars_with_prefix(&self, prefix: &str) -> Vec<(String, String)> {
+        self.vars
+            .range(prefix.to_string()..)
+            .take_while(|(name, _)| name.starts_with(prefix))
+            .map(|(name, (value, _))| (name.clone(), value.clone()))
+            .collect()
+    }
+
+    fn origin(&self, name: &str) -> Option<String> {
+        let line = self.line(name)?;
+        Some(format!("{}:{}", display_path(self.path()), line))
+    }
+}
+
+struct Parser<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+    column: usize,
+    path: Option<&'a Path>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(content: &str, path: Option<&'a Path>) -> Self {
+        Parser {
+            chars: content.chars().collect(),
+            pos: 0,
+            line: 1,
+            column: 1,
+            path,
+        }
+    }
+
+    fn parse(mut self) -> Result<DotEnv, DotEnvError> {
+        let mut vars = BTreeMap::new();
+
+        loop {
+            self.skip_inline_whitespace();
+            match self.peek() {
+                None => break,
+                Some('\n') | Some('\r') => {
+                    self.bump();
+                    continue;
+                }
+                Some('#') => {
+                    self.skip_comment();
+                    continue;
+                }
+                Some(_) => {}
+            }
+
+            let line = self.line;
+            let mut key = self.parse_key()?;
+            if key == "export" && matches!(self.peek(), Some(' ') | Some('\t')) {
+                self.skip_inline_whitespace();
+                key = self.parse_key()?;
+            }
+
+            self.skip_inline_whitespace();
+            if self.peek() != Some('=') {
+                return Err(self.error(format!("expected '=' after key '{}'", key)));
+            }
+            self.bump();
+            self.skip_inline_whitespace();
+
+            let value = match self.peek() {
+                Some('"') => self.parse_double_quoted()?,
+                Some('\'') => self.parse_single_quoted()?,
+                _ => self.parse_unquoted(),
+            };
+            self.expect_end_of_line()?;
+
+            vars.insert(key, (value, line));
+        }
+
+        Ok(DotEnv {
+            path: self.path.map(Path::to_path_buf),
+            vars,
+        })
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let ch = self.peek()?;
+        self.pos += 1;
+        if ch == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(ch)
+    }
+
+    fn error(&self, message: impl Into<String>) -> DotEnvError {
+        self.error_at(self.line, self.column, message)
+    }
+
+    fn error_at(&self, line: usize, column: usize, message: impl Into<String>) -> DotEnvError {
+        DotEnvError::Syntax {
+            path: self.path.map(Path::to_path_buf),
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    fn skip_inline_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ') | Some('\t')) {
+            self.bump();
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        while !matches!(self.peek(), None | Some('\n')) {
+            self.bump();
+        }
+    }
+
+    fn parse_key(&mut self) -> Result<String, DotEnvError> {
+        let mut key = String::new();
+        while let Some(ch) = self.peek() {
+            let valid = if key.is_empty() {
+                ch.is_ascii_alphabetic() || ch == '_'
+            } else {
+                ch.is_ascii_alphanumeric() || ch == '_' || ch == '.'
+            };
+            if !valid {
+                break;
+            }
+            key.push(ch);
+            self.bump();
+        }
+
+        if key.is_empty() {
+            return Err(match self.peek() {
+                Some(ch) => self.error(format!("invalid character '{}' at start of key", ch)),
+                None => self.error("expected a key"),
+            });
+        }
+        Ok(key)
+    }
+
+    fn parse_unquoted(&mut self) -> String {
+        let mut value = String::new();
+        while let Some(ch) = self.peek() {
+            if ch == '\n' || ch == '\r' {
+                break;
+            }
+            // `#` only starts a comment after whitespace, so `A=b#c` keeps the `#`
+            if ch == '#' && (value.is_empty() || value.ends_with([' ', '\t'])) {
+                break;
+            }
+            value.push(ch);
+            self.bump();
+        }
+        value.trim_end().to_string()
+    }
+
+    fn parse_single_quoted(&mut self) -> Result<String, DotEnvError> {
+        let (line, column) = (self.line, self.column);
+        self.bump();
+
+        let mut value = String::new();
+        loop {
+            match self.bump() {
+                Some('\'') => return Ok(value),
+                Some(ch) => value.push(ch),
+                None => return Err(self.error_at(line, column, "unterminated single-quoted value")),
+            }
+        }
+    }
+
+    fn parse_double_quoted(&mut self) -> Result<String, DotEnvError> {
+        let (line, column) = (self.line, self.column);
+        self.bump();
+
+        let mut value = String::new();
+        loop {
+            match self.bump() {
+                Some('"') => return Ok(value),
+                Some('\\') => {
+                    let (escape_line, escape_column) = (self.line, self.column - 1);
+                    let escaped = match self.bump() {
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('\\') => '\\',
+                        Some('"') => '"',
+                        Some('$') => '$',
+                        Some(other) => {
+                            return Err(self.error_at(
+                                escape_line,
+                                escape_column,
+                                format!("invalid escape sequence '\\{}'", other),
+                            ))
+                        }
+                        None => {
+                            return Err(self.error_at(line, column, "unterminated double-quoted value"))
+                        }
+                    };
+                    value.push(escaped);
+                }
+                Some(ch) => value.push(ch),
+                None => return Err(self.error_at(line, column, "unterminated double-quoted value")),
+            }
+        }
+    }
+
+    fn expect_end_of_line(&mut self) -> Result<(), DotEnvError> {
+        self.skip_inline_whitespace();
+        match self.peek() {
+            None | Some('\n') | Some('\r') => Ok(()),
+            Some('#') => {
+                self.skip_comment();
+                Ok(())
+            }
+            Some(ch) => Err(self.error(format!("unexpected character '{}' after value", ch))),
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use crate::ParseError;
+
+/// How a bytes field (`Vec<u8>`, `bytes::Bytes`) is written in its variable.
+///
+/// Fields use `Base64` unless they set `#[from_env(encoding = "...")]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BytesEncoding {
+    /// Standard base64 (`+`, `/`), padding optional.
+    Base64,
+    /// URL-safe base64 (`-`, `_`), padding optional.
+    Base64Url,
+    /// Hexadecimal, two digits per byte, either case.
+    Hex,
+    /// The variable's UTF-8 bytes as they are.
+    Raw,
+}
+
+impl std::fmt::Display for BytesEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            BytesEncoding::Base64 => "base64",
+            BytesEncoding::Base64Url => "base64url",
+            BytesEncoding::Hex => "hex",
+            BytesEncoding::Raw => "raw",
+        })
+    }
+}
+
+/// Decodes a bytes value. Surrounding whitespace is ignored except for
+/// `Raw`, and error offsets count bytes from the start of `s`.
+pub fn parse_bytes(s: &str, encoding: BytesEncoding) -> Result<Vec<u8>, ParseError> {
+    if encoding == BytesEncoding::Raw {
+        return Ok(s.as_bytes().to_vec());
+    }
+
+    let trimmed = s.trim();
+    let start = s.len() - s.trim_start().len();
+    let invalid = |offset: usize, message: String| ParseError::InvalidBytes {
+        encoding,
+        offset: start + offset,
+        message,
+    };
+    let unexpected = |offset: usize| {
+        let ch = trimmed[offset..].chars().next().unwrap();
+        invalid(offset, format!("unexpected character '{}'", ch))
+    };
+
+    match encoding {
+        BytesEncoding::Hex => {
+            let input = trimmed.as_bytes();
+            let mut bytes = Vec::with_capacity(input.len() / 2);
+            for (i, pair) in input.chunks(2).enumerate() {
+                let high = hex_digit(pair[0]).ok_or_else(|| unexpected(i * 2))?;
+                let low = match pair.get(1) {
+                    Some(&digit) => hex_digit(digit).ok_or_else(|| unexpected(i * 2 + 1))?,
+                    None => return Err(invalid(i * 2, "odd number of hex digits".to_string())),
+                };
+                bytes.push(high << 4 | low);
+            }
+            Ok(bytes)
+        }
+        _ => {
+            let url_safe = encoding == BytesEncoding::Base64Url;
+            let input = trimmed.as_bytes();
+            let end = input.iter().rposition(|&b| b != b'=').map_or(0, |i| i + 1);
+            let padding = input.len() - end;
+            if padding > 2 || (padding > 0 && !input.len().is_multiple_of(4)) {
+                return Err(invalid(end, "invalid padding".to_string()));
+            }
+
+            let mut bytes = Vec::with_capacity(end * 3 / 4);
+            let mut buffer = 0u32;
+            let mut bits = 0;
+            for (i, &b) in input[..end].iter().enumerate() {
+                let value = match b {
+                    b'A'..=b'Z' => b - b'A',
+                    b'a'..=b'z' => b - b'a' + 26,
+                    b'0'..=b'9' => b - b'0' + 52,
+                    b'+' if !url_safe => 62,
+                    b'/' if !url_safe => 63,
+                    b'-' if url_safe => 62,
+                    b'_' if url_safe => 63,
+                    _ => return Err(unexpected(i)),
+                };
+                buffer = buffer << 6 | value as u32;
+                bits += 6;
+                if bits >= 8 {
+                    bits -= 8;
+                    bytes.push((buffer >> bits) as u8);
+                    buffer &= (1 << bits) - 1;
+                }
+            }
+            if end % 4 == 1 {
+                return Err(invalid(end - 1, "truncated input".to_string()));
+            }
+            Ok(bytes)
+        }
+    }
+}
+
+fn hex_digit(b: u8) -> Option<u8> {
+    match b {
+        b'0'..=b'9' => Some(b - b'0'),
+        b'a'..=b'f' => Some(b - b'a' + 10),
+        b'A'..=b'F' => Some(b - b'A' + 10),
+        _ => None,
+    }
+}
@@ -1,5 +1,19 @@
 pub use from_env_derive::FromEnv;
 
+mod deprecation;
+mod dotenv;
+mod encoding;
+mod source;
+#[cfg(feature = "prost-types")]
+mod well_known;
+
+pub use deprecation::{set_deprecation_hook, Deprecation};
+pub use dotenv::{DotEnv, DotEnvError};
+pub use encoding::{parse_bytes, BytesEncoding};
+pub use source::{EnvSource, Layered, MapEnv, ProcessEnv};
+#[cfg(feature = "prost-types")]
+pub use well_known::{parse_duration, parse_timestamp};
+
 use std::num::{ParseFloatError, ParseIntError};
 use thiserror::Error;
 
@@ -13,6 +27,105 @@ pub enum FromEnvError {
         var: String,
         source: ParseError,
     },
+
+    #[error(
+        "Optional group '{prefix}' is only partially configured: set [{}], missing [{}]",
+        .set.join(", "),
+        .missing.join(", ")
+    )]
+    PartiallyConfigured {
+        prefix: String,
+        set: Vec<String>,
+        missing: Vec<String>,
+    },
+
+    #[error(
+        "'{prefix}' sets index {next} but not index {missing}; indexes must start at 0 with no gaps"
+    )]
+    IndexGap {
+        prefix: String,
+        missing: usize,
+        next: usize,
+    },
+
+    #[error("'{prefix}' index {index} is set more than once, as [{}]", .spellings.join(", "))]
+    DuplicateIndex {
+        prefix: String,
+        index: usize,
+        spellings: Vec<String>,
+    },
+
+    #[error("'{prefix}' sets index {index}, but its count is {count}")]
+    IndexOutOfRange {
+        prefix: String,
+        index: usize,
+        count: usize,
+    },
+
+    #[error("{origin}: {source}")]
+    Located {
+        origin: String,
+        source: Box<FromEnvError>,
+    },
+}
+
+/// Every error found while loading a struct, from the `*_all_errors` methods.
+#[derive(Debug)]
+pub struct FromEnvErrors(Vec<FromEnvError>);
+
+impl FromEnvErrors {
+    pub fn errors(&self) -> &[FromEnvError] {
+        &self.0
+    }
+
+    pub fn into_errors(self) -> Vec<FromEnvError> {
+        self.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, FromEnvError> {
+        self.0.iter()
+    }
+}
+
+impl std::fmt::Display for FromEnvErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0.len() {
+            1 => write!(f, "1 environment variable error:")?,
+            n => write!(f, "{} environment variable errors:", n)?,
+        }
+        for error in &self.0 {
+            write!(f, "\n  - {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for FromEnvErrors {}
+
+impl IntoIterator for FromEnvErrors {
+    type Item = FromEnvError;
+    type IntoIter = std::vec::IntoIter<FromEnvError>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a FromEnvErrors {
+    type Item = &'a FromEnvError;
+    type IntoIter = std::slice::Iter<'a, FromEnvError>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
 }
 
 #[derive(Debug, Error)]
@@ -32,43 +145,921 @@ pub enum ParseError {
     #[error("Failed to parse vector element at index {index}: {error}")]
     ParseVec { index: usize, error: String },
 
+    #[error("Failed to parse map entry '{entry}': {error}")]
+    ParseMap { entry: String, error: String },
+
+    #[error("Invalid {encoding} at byte {offset}: {message}")]
+    InvalidBytes {
+        encoding: BytesEncoding,
+        offset: usize,
+        message: String,
+    },
+
+    #[cfg(feature = "prost-types")]
+    #[error("Failed to parse duration '{value}': {message}")]
+    InvalidDuration { value: String, message: String },
+
+    #[cfg(feature = "prost-types")]
+    #[error("Failed to parse timestamp '{value}': {message}")]
+    InvalidTimestamp { value: String, message: String },
+
     #[error("Invalid UTF-8 in environment variable")]
     InvalidUtf8,
+
+    #[error(
+        "Failed to parse {enum_name}: expected a number or one of [{}], got '{value}'",
+        .valid.join(", ")
+    )]
+    InvalidEnum {
+        enum_name: &'static str,
+        value: String,
+        valid: Vec<String>,
+    },
+
+    #[error(
+        "Failed to parse {oneof}: expected one of [{}], got '{value}'",
+        .valid.join(", ")
+    )]
+    UnknownVariant {
+        oneof: &'static str,
+        value: String,
+        valid: Vec<String>,
+    },
+
+    #[error("Failed to parse {type_name}: {source}")]
+    Custom {
+        type_name: &'static str,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
 }
 
 pub trait FromEnvTrait {
     fn from_env() -> Result<Self, FromEnvError>
     where
-        Self: Sized;
+        Self: Sized,
+    {
+        Self::from_source(&ProcessEnv)
+    }
 
-    fn load_from_env(&mut self) -> Result<(), FromEnvError>;
+    fn load_from_env(&mut self) -> Result<(), FromEnvError> {
+        self.load_from_source(&ProcessEnv)
+    }
 
     fn from_env_with_prefix(prefix: &str) -> Result<Self, FromEnvError>
+    where
+        Self: Sized,
+    {
+        Self::from_source_with_prefix(&ProcessEnv, prefix)
+    }
+
+    fn load_from_env_with_prefix(&mut self, prefix: &str) -> Result<(), FromEnvError> {
+        self.load_from_source_with_prefix(&ProcessEnv, prefix)
+    }
+
+    /// Like [`from_env`](FromEnvTrait::from_env), but reads variables from `source`
+    /// instead of the process environment.
+    fn from_source(source: &impl EnvSource) -> Result<Self, FromEnvError>
+    where
+        Self: Sized,
+    {
+        Self::from_source_with_prefix(source, "")
+    }
+
+    fn load_from_source(&mut self, source: &impl EnvSource) -> Result<(), FromEnvError> {
+        self.load_from_source_with_prefix(source, "")
+    }
+
+    /// Loads from the given `(name, value)` pairs alone, ignoring the process
+    /// environment. Handy for tests, which then don't race on `set_var`.
+    fn from_pairs<I, K, V>(pairs: I) -> Result<Self, FromEnvError>
+    where
+        Self: Sized,
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        Self::from_source(&pairs.into_iter().collect::<MapEnv>())
+    }
+
+    fn from_source_with_prefix(source: &impl EnvSource, prefix: &str) -> Result<Self, FromEnvError>
     where
         Self: Sized;
 
-    fn load_from_env_with_prefix(&mut self, prefix: &str) -> Result<(), FromEnvError>;
+    fn load_from_source_with_prefix(
+        &mut self,
+        source: &impl EnvSource,
+        prefix: &str,
+    ) -> Result<(), FromEnvError>;
+
+    /// Like [`from_env`](FromEnvTrait::from_env), but tries every field
+    /// (including nested ones) and reports every missing or malformed
+    /// variable instead of stopping at the first.
+    fn from_env_all_errors() -> Result<Self, FromEnvErrors>
+    where
+        Self: Sized,
+    {
+        Self::from_source_all_errors(&ProcessEnv)
+    }
+
+    fn from_env_with_prefix_all_errors(prefix: &str) -> Result<Self, FromEnvErrors>
+    where
+        Self: Sized,
+    {
+        Self::from_source_with_prefix_all_errors(&ProcessEnv, prefix)
+    }
+
+    fn from_source_all_errors(source: &impl EnvSource) -> Result<Self, FromEnvErrors>
+    where
+        Self: Sized,
+    {
+        Self::from_source_with_prefix_all_errors(source, "")
+    }
+
+    fn from_source_with_prefix_all_errors(
+        source: &impl EnvSource,
+        prefix: &str,
+    ) -> Result<Self, FromEnvErrors>
+    where
+        Self: Sized;
+
+    /// Internal method for nested struct handling - prefix already includes the full path.
+    /// `nesting_separator` comes from the outermost struct, so nested structs
+    /// join their segments the same way.
+    #[doc(hidden)]
+    fn from_env_with_prefix_internal(
+        source: &dyn EnvSource,
+        prefix: &str,
+        nesting_separator: &str,
+    ) -> Result<Self, FromEnvError>
+    where
+        Self: Sized;
+
+    #[doc(hidden)]
+    fn load_from_env_with_prefix_internal(
+        &mut self,
+        source: &dyn EnvSource,
+        prefix: &str,
+        nesting_separator: &str,
+    ) -> Result<(), FromEnvError>;
 
-    /// Internal method for nested struct handling - prefix already includes the full path
+    /// Loads every field, pushing failures onto `errors`. Returns `None` if any
+    /// field failed.
     #[doc(hidden)]
-    fn from_env_with_prefix_internal(prefix: &str) -> Result<Self, FromEnvError>
+    fn from_env_with_prefix_collect(
+        source: &dyn EnvSource,
+        prefix: &str,
+        nesting_separator: &str,
+        errors: &mut Vec<FromEnvError>,
+    ) -> Option<Self>
     where
         Self: Sized;
 
+    /// Every variable name the struct reads under `prefix`, paired with whether
+    /// it is required. Used to decide whether an optional nested struct is set.
+    /// Fields with aliases report whichever name `source` defines.
     #[doc(hidden)]
-    fn load_from_env_with_prefix_internal(&mut self, prefix: &str) -> Result<(), FromEnvError>;
+    fn env_vars_with_prefix_internal(
+        source: &dyn EnvSource,
+        prefix: &str,
+        nesting_separator: &str,
+    ) -> Vec<(String, bool)>;
+}
+
+/// Implemented by `#[derive(FromEnv)]` for prost oneof enums.
+///
+/// A struct field tagged `#[prost(oneof = "...")]` reads the variant name from
+/// its own variable (`APPCONFIG_KIND=postgres`), then loads that variant's
+/// payload from underneath it (`APPCONFIG_KIND_POSTGRES_*`).
+pub trait FromEnvOneof {
+    /// Loads the variant named by `value`. `discriminator` is the variable
+    /// `value` was read from; the payload is read from `discriminator`,
+    /// `nesting_separator` and the variant name.
+    #[doc(hidden)]
+    fn from_env_variant_internal(
+        source: &dyn EnvSource,
+        discriminator: &str,
+        nesting_separator: &str,
+        value: &str,
+    ) -> Result<Self, FromEnvError>
+    where
+        Self: Sized;
+}
+
+/// Support code for `#[derive(FromEnv)]`. Not part of the public API.
+///
+/// A derive can't see whether a field's type implements [`FromEnvTrait`], so
+/// fields that aren't a built-in primitive are dispatched through
+/// [`Field`](__private::Field) using autoref specialization: types
+/// implementing `FromEnvTrait` are loaded as nested structs, and anything else
+/// falls back to `FromStr`.
+#[doc(hidden)]
+pub mod __private {
+    use super::{parse_map_entries, parse_map_entry, EnvSource, FromEnvError, FromEnvErrors, FromEnvTrait, ParseError};
+    use std::collections::{BTreeMap, BTreeSet};
+    use std::marker::PhantomData;
+
+    /// Wraps a parse error in [`FromEnvError::Located`] if `source` knows where
+    /// the offending value came from.
+    pub fn locate(source: &dyn EnvSource, error: FromEnvError) -> FromEnvError {
+        let origin = match &error {
+            FromEnvError::ParseError { var, .. } => source.origin(var),
+            _ => None,
+        };
+        match origin {
+            Some(origin) => FromEnvError::Located {
+                origin,
+                source: Box::new(error),
+            },
+            None => error,
+        }
+    }
+
+    /// Builds the result of a `*_all_errors` method from what was collected.
+    pub fn all_errors<T>(
+        source: &dyn EnvSource,
+        value: Option<T>,
+        errors: Vec<FromEnvError>,
+    ) -> Result<T, FromEnvErrors> {
+        match value {
+            Some(value) if errors.is_empty() => Ok(value),
+            _ => Err(FromEnvErrors(
+                errors.into_iter().map(|e| locate(source, e)).collect(),
+            )),
+        }
+    }
+
+    pub fn collect<T>(result: Result<T, FromEnvError>, errors: &mut Vec<FromEnvError>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(e) => {
+                errors.push(e);
+                None
+            }
+        }
+    }
+
+    /// The prefix of an optional nested struct if it should be loaded: `None`
+    /// if none of its variables are set, `Some` if all the required ones are,
+    /// and `PartiallyConfigured` otherwise.
+    fn optional_prefix<T: FromEnvTrait>(
+        source: &dyn EnvSource,
+        var_name: &str,
+        nesting_separator: &str,
+    ) -> Result<Option<String>, FromEnvError> {
+        let prefix = format!("{}{}", var_name, nesting_separator);
+        Ok(is_configured::<T>(source, &prefix, nesting_separator)?.then_some(prefix))
+    }
+
+    /// Whether an optional struct read from `prefix` is set; see `optional_prefix`.
+    fn is_configured<T: FromEnvTrait>(
+        source: &dyn EnvSource,
+        prefix: &str,
+        nesting_separator: &str,
+    ) -> Result<bool, FromEnvError> {
+        let vars = T::env_vars_with_prefix_internal(source, prefix, nesting_separator);
+
+        let set: Vec<String> = vars
+            .iter()
+            .filter(|(name, _)| source.get(name).is_some())
+            .map(|(name, _)| name.clone())
+            .collect();
+        if set.is_empty() {
+            return Ok(false);
+        }
+
+        let missing: Vec<String> = vars
+            .iter()
+            .filter(|(name, required)| *required && source.get(name).is_none())
+            .map(|(name, _)| name.clone())
+            .collect();
+        if !missing.is_empty() {
+            return Err(FromEnvError::PartiallyConfigured {
+                prefix: prefix.to_string(),
+                set,
+                missing,
+            });
+        }
+
+        Ok(true)
+    }
+
+    /// Loads an `Option<T>` field marked `#[from_env(flatten)]`, whose fields
+    /// sit directly under the parent's `prefix`.
+    pub fn flatten_optional<T: FromEnvTrait>(
+        source: &dyn EnvSource,
+        prefix: &str,
+        nesting_separator: &str,
+    ) -> Result<Option<T>, FromEnvError> {
+        if is_configured::<T>(source, prefix, nesting_separator)? {
+            T::from_env_with_prefix_internal(source, prefix, nesting_separator).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn flatten_optional_collect<T: FromEnvTrait>(
+        source: &dyn EnvSource,
+        prefix: &str,
+        nesting_separator: &str,
+        errors: &mut Vec<FromEnvError>,
+    ) -> Option<Option<T>> {
+        if collect(is_configured::<T>(source, prefix, nesting_separator), errors)? {
+            T::from_env_with_prefix_collect(source, prefix, nesting_separator, errors).map(Some)
+        } else {
+            Some(None)
+        }
+    }
+
+    /// Loads a map field from `var_name` itself (`k=v,k2=v2`) and from every
+    /// `{var_name}_<KEY>` variable, whose entries win over inline ones. `None`
+    /// if neither form is set. Inline keys are kept as written; keys taken
+    /// from variable names are lowercased, so `{var_name}_HTTP` and `http=80`
+    /// name the same entry, and their original case is lost. `field_vars` are
+    /// the struct's field variables; those that share the map's prefix, and
+    /// anything nested under them, aren't map entries.
+    pub fn map<M, K, V>(
+        source: &dyn EnvSource,
+        var_name: &str,
+        nesting_separator: &str,
+        field_vars: &[String],
+    ) -> Result<Option<M>, FromEnvError>
+    where
+        M: FromIterator<(K, V)>,
+        K: std::str::FromStr,
+        K::Err: std::fmt::Display,
+        V: std::str::FromStr,
+        V::Err: std::fmt::Display,
+    {
+        let inline = source.get(var_name);
+        let (prefix, scanned) = map_entry_vars(source, var_name, nesting_separator, field_vars);
+        if inline.is_none() && scanned.is_empty() {
+            return Ok(None);
+        }
+
+        let mut entries: Vec<(K, V)> = match inline {
+            Some(value) => parse_map_entries(&value).map_err(|e| FromEnvError::ParseError {
+                var: var_name.to_string(),
+                source: e,
+            })?,
+            None => Vec::new(),
+        };
+        for (name, value) in scanned {
+            let entry = parse_map_entry(&name[prefix.len()..], &value, str::to_lowercase).map_err(|e| {
+                FromEnvError::ParseError {
+                    var: name.clone(),
+                    source: e,
+                }
+            })?;
+            entries.push(entry);
+        }
+
+        Ok(Some(entries.into_iter().collect()))
+    }
+
+    /// The variables a map field is read from, for `env_vars_with_prefix_internal`.
+    pub fn map_vars(
+        source: &dyn EnvSource,
+        var_name: &str,
+        nesting_separator: &str,
+        field_vars: &[String],
+        required: bool,
+    ) -> Vec<(String, bool)> {
+        let mut vars = vec![(var_name.to_string(), required)];
+        let (_, scanned) = map_entry_vars(source, var_name, nesting_separator, field_vars);
+        vars.extend(scanned.into_iter().map(|(name, _)| (name, false)));
+        vars
+    }
+
+    /// The `{var_name}_<KEY>` variables of a map field, along with their
+    /// common prefix. A sibling field named `{var_name}_COUNT` and anything
+    /// under it belongs to that field instead.
+    fn map_entry_vars(
+        source: &dyn EnvSource,
+        var_name: &str,
+        nesting_separator: &str,
+        field_vars: &[String],
+    ) -> (String, Vec<(String, String)>) {
+        let prefix = format!("{}{}", var_name, nesting_separator);
+        let siblings: Vec<&String> = field_vars
+            .iter()
+            .filter(|field_var| field_var.as_str() != var_name && field_var.starts_with(&prefix))
+            .collect();
+        let scanned = source
+            .vars_with_prefix(&prefix)
+            .into_iter()
+            .filter(|(name, _)| name.len() > prefix.len())
+            .filter(|(name, _)| {
+                !siblings.iter().any(|sibling| {
+                    name == *sibling
+                        || name
+                            .strip_prefix(sibling.as_str())
+                            .is_some_and(|rest| rest.starts_with(nesting_separator))
+                })
+            })
+            .collect();
+        (prefix, scanned)
+    }
+
+    /// Picks the name a field is read from: `primary` if `source` defines it,
+    /// otherwise the first alias or deprecated name that is defined, otherwise
+    /// `primary` so a missing variable is reported under its main name. When
+    /// `report` is set, falling back to a deprecated name calls the
+    /// deprecation hook.
+    pub fn resolve_name<'a>(
+        source: &dyn EnvSource,
+        primary: &'a str,
+        aliases: &[&'a str],
+        deprecated: &[&'a str],
+        report: bool,
+    ) -> &'a str {
+        if source.get(primary).is_some() {
+            return primary;
+        }
+        if let Some(alias) = aliases.iter().find(|name| source.get(name).is_some()) {
+            return alias;
+        }
+        if let Some(old) = deprecated.iter().find(|name| source.get(name).is_some()) {
+            if report {
+                crate::deprecation::report(&crate::Deprecation {
+                    name: old,
+                    replacement: primary,
+                });
+            }
+            return old;
+        }
+        primary
+    }
+
+    /// `source` with `name` defaulting to `value`, for `#[from_env(default = "...")]`.
+    pub struct WithDefault<'a> {
+        source: &'a dyn EnvSource,
+        name: &'a str,
+        value: &'static str,
+    }
+
+    impl<'a> WithDefault<'a> {
+        pub fn new(source: &'a dyn EnvSource, name: &'a str, value: &'static str) -> Self {
+            WithDefault { source, name, value }
+        }
+    }
+
+    impl EnvSource for WithDefault<'_> {
+        fn get(&self, name: &str) -> Option<String> {
+            self.source
+                .get(name)
+                .or_else(|| (name == self.name).then(|| self.value.to_string()))
+        }
+
+        fn vars_with_prefix(&self, prefix: &str) -> Vec<(String, String)> {
+            self.source.vars_with_prefix(prefix)
+        }
+
+        fn origin(&self, name: &str) -> Option<String> {
+            self.source.origin(name)
+        }
+    }
+
+    /// Implemented by `#[derive(FromEnv)]` for enums with only unit variants,
+    /// i.e. proto enums, which prost gives no way to enumerate.
+    pub trait ProstEnum: Sized + 'static {
+        const VARIANTS: &'static [Self];
+    }
+
+    /// Parses a prost enumeration field from a number or a variant name.
+    /// Names are case-insensitive and may omit or include the enum-name prefix
+    /// (`DEBUG` or `LOG_LEVEL_DEBUG` for `LogLevel`).
+    pub fn parse_prost_enum<E>(
+        value: &str,
+        enum_name: &'static str,
+        variant_prefix: &str,
+        from_str_name: fn(&str) -> Option<E>,
+        as_str_name: fn(&E) -> &'static str,
+    ) -> Result<i32, ParseError>
+    where
+        E: ProstEnum + TryFrom<i32> + Into<i32>,
+    {
+        let value = value.trim();
+        let invalid = || ParseError::InvalidEnum {
+            enum_name,
+            value: value.to_string(),
+            valid: E::VARIANTS.iter().map(|e| as_str_name(e).to_string()).collect(),
+        };
+
+        if let Ok(number) = value.parse::<i32>() {
+            return match E::try_from(number) {
+                Ok(_) => Ok(number),
+                Err(_) => Err(invalid()),
+            };
+        }
+
+        let upper = value.to_uppercase();
+        let unprefixed = upper.strip_prefix(variant_prefix).unwrap_or(&upper);
+        [
+            upper.clone(),
+            format!("{}{}", variant_prefix, upper),
+            unprefixed.to_string(),
+        ]
+        .iter()
+        .find_map(|name| from_str_name(name))
+        .map(Into::into)
+        .ok_or_else(invalid)
+    }
+
+    /// `nesting_separator` joins the field's name and what lives underneath
+    /// it: a nested struct's fields or an indexed list's elements.
+    /// `type_name` is `T` as written in the struct, for parse errors.
+    pub struct Field<'a, T> {
+        nesting_separator: &'a str,
+        type_name: &'static str,
+        marker: PhantomData<T>,
+    }
+
+    impl<'a, T> Field<'a, T> {
+        pub fn new(nesting_separator: &'a str, type_name: &'static str) -> Self {
+            Field {
+                nesting_separator,
+                type_name,
+                marker: PhantomData,
+            }
+        }
+    }
+
+    /// Picked when the field type implements `FromEnvTrait`. `var_name` is the
+    /// name the field would have as a scalar; the nested struct's fields live
+    /// underneath it.
+    pub trait ViaFromEnv<T> {
+        fn required(&self, source: &dyn EnvSource, var_name: &str) -> Result<T, FromEnvError>;
+        fn optional(&self, source: &dyn EnvSource, var_name: &str) -> Result<Option<T>, FromEnvError>;
+        fn required_collect(
+            &self,
+            source: &dyn EnvSource,
+            var_name: &str,
+            errors: &mut Vec<FromEnvError>,
+        ) -> Option<T>;
+        fn optional_collect(
+            &self,
+            source: &dyn EnvSource,
+            var_name: &str,
+            errors: &mut Vec<FromEnvError>,
+        ) -> Option<Option<T>>;
+        fn vars(&self, source: &dyn EnvSource, var_name: &str, required: bool) -> Vec<(String, bool)>;
+        /// A `Vec<T>` loaded from `{var_name}_0_*`, `{var_name}_1_*`, ...
+        fn vec(&self, source: &dyn EnvSource, var_name: &str) -> Result<Vec<T>, FromEnvError>;
+        fn vec_collect(
+            &self,
+            source: &dyn EnvSource,
+            var_name: &str,
+            errors: &mut Vec<FromEnvError>,
+        ) -> Option<Vec<T>>;
+        fn vec_vars(&self, source: &dyn EnvSource, var_name: &str, required: bool) -> Vec<(String, bool)>;
+    }
+
+    impl<T: FromEnvTrait> ViaFromEnv<T> for Field<'_, T> {
+        fn required(&self, source: &dyn EnvSource, var_name: &str) -> Result<T, FromEnvError> {
+            T::from_env_with_prefix_internal(
+                source,
+                &format!("{}{}", var_name, self.nesting_separator),
+                self.nesting_separator,
+            )
+        }
+
+        fn optional(&self, source: &dyn EnvSource, var_name: &str) -> Result<Option<T>, FromEnvError> {
+            match optional_prefix::<T>(source, var_name, self.nesting_separator)? {
+                Some(prefix) => T::from_env_with_prefix_internal(source, &prefix, self.nesting_separator).map(Some),
+                None => Ok(None),
+            }
+        }
+
+        fn required_collect(
+            &self,
+            source: &dyn EnvSource,
+            var_name: &str,
+            errors: &mut Vec<FromEnvError>,
+        ) -> Option<T> {
+            T::from_env_with_prefix_collect(
+                source,
+                &format!("{}{}", var_name, self.nesting_separator),
+                self.nesting_separator,
+                errors,
+            )
+        }
+
+        fn optional_collect(
+            &self,
+            source: &dyn EnvSource,
+            var_name: &str,
+            errors: &mut Vec<FromEnvError>,
+        ) -> Option<Option<T>> {
+            match collect(optional_prefix::<T>(source, var_name, self.nesting_separator), errors)? {
+                Some(prefix) => T::from_env_with_prefix_collect(source, &prefix, self.nesting_separator, errors).map(Some),
+                None => Some(None),
+            }
+        }
+
+        fn vars(&self, source: &dyn EnvSource, var_name: &str, required: bool) -> Vec<(String, bool)> {
+            T::env_vars_with_prefix_internal(
+                source,
+                &format!("{}{}", var_name, self.nesting_separator),
+                self.nesting_separator,
+            )
+                .into_iter()
+                .map(|(name, child_required)| (name, required && child_required))
+                .collect()
+        }
+
+        fn vec(&self, source: &dyn EnvSource, var_name: &str) -> Result<Vec<T>, FromEnvError> {
+            indexed_prefixes(source, var_name, self.nesting_separator)?
+                .iter()
+                .map(|prefix| T::from_env_with_prefix_internal(source, prefix, self.nesting_separator))
+                .collect()
+        }
+
+        fn vec_collect(
+            &self,
+            source: &dyn EnvSource,
+            var_name: &str,
+            errors: &mut Vec<FromEnvError>,
+        ) -> Option<Vec<T>> {
+            let prefixes = collect(indexed_prefixes(source, var_name, self.nesting_separator), errors)?;
+            let mut items = Vec::with_capacity(prefixes.len());
+            let mut complete = true;
+            for prefix in &prefixes {
+                match T::from_env_with_prefix_collect(source, prefix, self.nesting_separator, errors) {
+                    Some(item) => items.push(item),
+                    None => complete = false,
+                }
+            }
+            complete.then_some(items)
+        }
+
+        fn vec_vars(&self, source: &dyn EnvSource, var_name: &str, _required: bool) -> Vec<(String, bool)> {
+            // An empty list is valid, so nothing here is required
+            let mut vars = vec![(format!("{}{}COUNT", var_name, self.nesting_separator), false)];
+            for prefix in indexed_prefixes(source, var_name, self.nesting_separator).unwrap_or_default() {
+                vars.extend(
+                    T::env_vars_with_prefix_internal(source, &prefix, self.nesting_separator)
+                        .into_iter()
+                        .map(|(name, _)| (name, false)),
+                );
+            }
+            vars
+        }
+    }
+
+    /// The prefixes of an indexed `Vec<T>` field's elements, `{var_name}_0_`,
+    /// `{var_name}_1_`, ... in order. `{var_name}_COUNT` fixes the length;
+    /// without it the list runs up to the last index that is set, which must
+    /// leave no gaps.
+    fn indexed_prefixes(
+        source: &dyn EnvSource,
+        var_name: &str,
+        nesting_separator: &str,
+    ) -> Result<Vec<String>, FromEnvError> {
+        let prefix = format!("{}{}", var_name, nesting_separator);
+
+        // Every spelling of each index, so `_1_` and `_01_` can be told apart
+        let mut spellings: BTreeMap<usize, BTreeSet<String>> = BTreeMap::new();
+        for (name, _) in source.vars_with_prefix(&prefix) {
+            let rest = &name[prefix.len()..];
+            if let Some((digits, _)) = rest.split_once(nesting_separator) {
+                if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) {
+                    if let Ok(index) = digits.parse::<usize>() {
+                        spellings.entry(index).or_default().insert(digits.to_string());
+                    }
+                }
+            }
+        }
+        if let Some((&index, digits)) = spellings.iter().find(|(_, digits)| digits.len() > 1) {
+            return Err(FromEnvError::DuplicateIndex {
+                prefix: prefix.clone(),
+                index,
+                spellings: digits.iter().map(|d| format!("{}{}{}", prefix, d, nesting_separator)).collect(),
+            });
+        }
+
+        let count_var = format!("{}COUNT", prefix);
+        let len = match source.get(&count_var) {
+            Some(value) => {
+                let count = value.trim().parse::<usize>().map_err(|e| FromEnvError::ParseError {
+                    var: count_var.clone(),
+                    source: ParseError::ParseInt(e),
+                })?;
+                if let Some((&index, _)) = spellings.range(count..).next() {
+                    return Err(FromEnvError::IndexOutOfRange { prefix, index, count });
+                }
+                count
+            }
+            None => {
+                let len = spellings.keys().zip(0..).take_while(|(index, expected)| **index == *expected).count();
+                if let Some(&next) = spellings.keys().nth(len) {
+                    return Err(FromEnvError::IndexGap {
+                        prefix,
+                        missing: len,
+                        next,
+                    });
+                }
+                len
+            }
+        };
+
+        Ok((0..len)
+            .map(|index| match spellings.get(&index).and_then(|digits| digits.first()) {
+                Some(digits) => format!("{}{}{}", prefix, digits, nesting_separator),
+                None => format!("{}{}{}", prefix, index, nesting_separator),
+            })
+            .collect())
+    }
+
+    /// Fallback for any other type, parsed from a single variable via `FromStr`.
+    pub trait ViaFromStr<T> {
+        fn required(&self, source: &dyn EnvSource, var_name: &str) -> Result<T, FromEnvError>;
+        fn optional(&self, source: &dyn EnvSource, var_name: &str) -> Result<Option<T>, FromEnvError>;
+        fn vars(&self, source: &dyn EnvSource, var_name: &str, required: bool) -> Vec<(String, bool)>;
+        /// A comma-separated `Vec<T>`.
+        fn vec(&self, source: &dyn EnvSource, var_name: &str) -> Result<Vec<T>, FromEnvError>;
+
+        fn vec_collect(
+            &self,
+            source: &dyn EnvSource,
+            var_name: &str,
+            errors: &mut Vec<FromEnvError>,
+        ) -> Option<Vec<T>> {
+            collect(self.vec(source, var_name), errors)
+        }
+
+        fn vec_vars(&self, source: &dyn EnvSource, var_name: &str, required: bool) -> Vec<(String, bool)> {
+            self.vars(source, var_name, required)
+        }
+
+        fn required_collect(
+            &self,
+            source: &dyn EnvSource,
+            var_name: &str,
+            errors: &mut Vec<FromEnvError>,
+        ) -> Option<T> {
+            collect(self.required(source, var_name), errors)
+        }
+
+        fn optional_collect(
+            &self,
+            source: &dyn EnvSource,
+            var_name: &str,
+            errors: &mut Vec<FromEnvError>,
+        ) -> Option<Option<T>> {
+            collect(self.optional(source, var_name), errors)
+        }
+    }
+
+    impl<T> ViaFromStr<T> for &Field<'_, T>
+    where
+        T: std::str::FromStr,
+        T::Err: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        fn required(&self, source: &dyn EnvSource, var_name: &str) -> Result<T, FromEnvError> {
+            let value = source
+                .get(var_name)
+                .ok_or_else(|| FromEnvError::MissingVariable(var_name.to_string()))?;
+            value.parse::<T>().map_err(|e| FromEnvError::ParseError {
+                var: var_name.to_string(),
+                source: ParseError::Custom {
+                    type_name: self.type_name,
+                    source: e.into(),
+                },
+            })
+        }
+
+        fn optional(&self, source: &dyn EnvSource, var_name: &str) -> Result<Option<T>, FromEnvError> {
+            match source.get(var_name) {
+                Some(_) => self.required(source, var_name).map(Some),
+                None => Ok(None),
+            }
+        }
+
+        fn vars(&self, _source: &dyn EnvSource, var_name: &str, required: bool) -> Vec<(String, bool)> {
+            vec![(var_name.to_string(), required)]
+        }
+
+        fn vec(&self, source: &dyn EnvSource, var_name: &str) -> Result<Vec<T>, FromEnvError> {
+            let value = source
+                .get(var_name)
+                .ok_or_else(|| FromEnvError::MissingVariable(var_name.to_string()))?;
+            super::parse_vec_with(&value, |item| {
+                item.parse::<T>().map_err(|e| ParseError::Custom {
+                    type_name: self.type_name,
+                    source: e.into(),
+                })
+            })
+            .map_err(|e| FromEnvError::ParseError {
+                var: var_name.to_string(),
+                source: e,
+            })
+        }
+    }
+
+    /// `prost_types` fields get inherent methods, which take precedence over
+    /// both dispatch traits, so they are parsed with `parse` instead of their
+    /// own `FromStr`.
+    #[cfg(feature = "prost-types")]
+    macro_rules! well_known_field {
+        ($ty:ty, $parse:path) => {
+            impl Field<'_, $ty> {
+                pub fn required(&self, source: &dyn EnvSource, var_name: &str) -> Result<$ty, FromEnvError> {
+                    let value = source
+                        .get(var_name)
+                        .ok_or_else(|| FromEnvError::MissingVariable(var_name.to_string()))?;
+                    $parse(&value).map_err(|e| FromEnvError::ParseError {
+                        var: var_name.to_string(),
+                        source: e,
+                    })
+                }
+
+                pub fn optional(&self, source: &dyn EnvSource, var_name: &str) -> Result<Option<$ty>, FromEnvError> {
+                    match source.get(var_name) {
+                        Some(_) => self.required(source, var_name).map(Some),
+                        None => Ok(None),
+                    }
+                }
+
+                pub fn required_collect(
+                    &self,
+                    source: &dyn EnvSource,
+                    var_name: &str,
+                    errors: &mut Vec<FromEnvError>,
+                ) -> Option<$ty> {
+                    collect(self.required(source, var_name), errors)
+                }
+
+                pub fn optional_collect(
+                    &self,
+                    source: &dyn EnvSource,
+                    var_name: &str,
+                    errors: &mut Vec<FromEnvError>,
+                ) -> Option<Option<$ty>> {
+                    collect(self.optional(source, var_name), errors)
+                }
+
+                pub fn vars(&self, _source: &dyn EnvSource, var_name: &str, required: bool) -> Vec<(String, bool)> {
+                    vec![(var_name.to_string(), required)]
+                }
+
+                pub fn vec(&self, source: &dyn EnvSource, var_name: &str) -> Result<Vec<$ty>, FromEnvError> {
+                    let value = source
+                        .get(var_name)
+                        .ok_or_else(|| FromEnvError::MissingVariable(var_name.to_string()))?;
+                    super::parse_vec_with(&value, $parse).map_err(|e| FromEnvError::ParseError {
+                        var: var_name.to_string(),
+                        source: e,
+                    })
+                }
+
+                pub fn vec_collect(
+                    &self,
+                    source: &dyn EnvSource,
+                    var_name: &str,
+                    errors: &mut Vec<FromEnvError>,
+                ) -> Option<Vec<$ty>> {
+                    collect(self.vec(source, var_name), errors)
+                }
+
+                pub fn vec_vars(&self, source: &dyn EnvSource, var_name: &str, required: bool) -> Vec<(String, bool)> {
+                    self.vars(source, var_name, required)
+                }
+            }
+        };
+    }
+
+    #[cfg(feature = "prost-types")]
+    well_known_field!(prost_types::Duration, crate::parse_duration);
+    #[cfg(feature = "prost-types")]
+    well_known_field!(prost_types::Timestamp, crate::parse_timestamp);
 }
 
 pub fn parse_env_var<T: std::str::FromStr>(var_name: &str) -> Result<T, FromEnvError>
 where
     T::Err: Into<ParseError>,
 {
-    let value = std::env::var(var_name)
-        .map_err(|_| FromEnvError::MissingVariable(var_name.to_string()))?;
+    parse_source_var(&ProcessEnv, var_name)
+}
 
-    value.parse::<T>().map_err(|e| FromEnvError::ParseError {
-        var: var_name.to_string(),
-        source: e.into(),
+pub fn parse_source_var<T: std::str::FromStr>(
+    source: &impl EnvSource,
+    var_name: &str,
+) -> Result<T, FromEnvError>
+where
+    T::Err: Into<ParseError>,
+{
+    let value = source
+        .get(var_name)
+        .ok_or_else(|| FromEnvError::MissingVariable(var_name.to_string()))?;
+
+    value.parse::<T>().map_err(|e| {
+        __private::locate(
+            source,
+            FromEnvError::ParseError {
+                var: var_name.to_string(),
+                source: e.into(),
+            },
+        )
     })
 }
 
@@ -162,3 +1153,71 @@ pub fn parse_vec_char(s: &str) -> Result<Vec<char>, ParseError> {
         }))
         .collect()
 }
+
+pub fn parse_vec_with<T, F>(s: &str, parse: F) -> Result<Vec<T>, ParseError>
+where
+    F: Fn(&str) -> Result<T, ParseError>,
+{
+    if s.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    s.split(',')
+        .map(|s| s.trim())
+        .enumerate()
+        .map(|(i, s)| parse(s).map_err(|e| ParseError::ParseVec {
+            index: i,
+            error: e.to_string(),
+        }))
+        .collect()
+}
+
+/// Parses `key=value,key2=value2` into a `HashMap`, `BTreeMap` or any other
+/// collection of pairs. Later entries win when a key repeats.
+pub fn parse_map<M, K, V>(s: &str) -> Result<M, ParseError>
+where
+    M: FromIterator<(K, V)>,
+    K: std::str::FromStr,
+    K::Err: std::fmt::Display,
+    V: std::str::FromStr,
+    V::Err: std::fmt::Display,
+{
+    parse_map_entries(s).map(|entries| entries.into_iter().collect())
+}
+
+fn parse_map_entries<K, V>(s: &str) -> Result<Vec<(K, V)>, ParseError>
+where
+    K: std::str::FromStr,
+    K::Err: std::fmt::Display,
+    V: std::str::FromStr,
+    V::Err: std::fmt::Display,
+{
+    if s.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    s.split(',')
+        .map(|entry| match entry.split_once('=') {
+            Some((key, value)) => parse_map_entry(key.trim(), value.trim(), str::to_string),
+            None => Err(ParseError::ParseMap {
+                entry: entry.trim().to_string(),
+                error: "expected 'key=value'".to_string(),
+            }),
+        })
+        .collect()
+}
+
+/// `map_key` normalizes the key before it is parsed; errors show it as written.
+fn parse_map_entry<K, V>(key: &str, value: &str, map_key: fn(&str) -> String) -> Result<(K, V), ParseError>
+where
+    K: std::str::FromStr,
+    K::Err: std::fmt::Display,
+    V: std::str::FromStr,
+    V::Err: std::fmt::Display,
+{
+    let error = |e: &dyn std::fmt::Display| ParseError::ParseMap {
+        entry: key.to_string(),
+        error: e.to_string(),
+    };
+    let parsed_key = map_key(key).parse::<K>().map_err(|e| error(&e))?;
+    let parsed_value = value.parse::<V>().map_err(|e| error(&e))?;
+    Ok((parsed_key, parsed_value))
+}
@@ -0,0 +1,225 @@
+use std::collections::{BTreeMap, HashMap};
+
+/// Where a derived struct reads its variables from.
+///
+/// [`ProcessEnv`] is the real process environment and is what
+/// [`from_env`](crate::FromEnvTrait::from_env) uses. Implement this trait to
+/// load from anything else, then call
+/// [`from_source`](crate::FromEnvTrait::from_source).
+pub trait EnvSource {
+    /// The value of `name`, or `None` if it isn't set.
+    fn get(&self, name: &str) -> Option<String>;
+
+    /// Every `(name, value)` pair whose name starts with `prefix`, sorted by name.
+    fn vars_with_prefix(&self, prefix: &str) -> Vec<(String, String)>;
+
+    /// Where the value of `name` came from, e.g. `app.env:12`. Parse errors for
+    /// variables with a known origin are reported as
+    /// [`FromEnvError::Located`](crate::FromEnvError::Located).
+    fn origin(&self, _name: &str) -> Option<String> {
+        None
+    }
+}
+
+/// The process environment, read through `std::env`.
+///
+/// Variables whose name or value is not valid UTF-8 are treated as unset.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProcessEnv;
+
+impl EnvSource for ProcessEnv {
+    fn get(&self, name: &str) -> Option<String> {
+        std::env::var(name).ok()
+    }
+
+    fn vars_with_prefix(&self, prefix: &str) -> Vec<(String, String)> {
+        let mut vars: Vec<(String, String)> = std::env::vars_os()
+            .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)))
+            .filter(|(name, _)| name.starts_with(prefix))
+            .collect();
+        vars.sort();
+        vars
+    }
+}
+
+impl<S: EnvSource + ?Sized> EnvSource for &S {
+    fn get(&self, name: &str) -> Option<String> {
+        (**self).get(name)
+    }
+
+    fn vars_with_prefix(&self, prefix: &str) -> Vec<(String, String)> {
+        (**self).vars_with_prefix(prefix)
+    }
+
+    fn origin(&self, name: &str) -> Option<String> {
+        (**self).origin(name)
+    }
+}
+
+impl<S: std::hash::BuildHasher> EnvSource for HashMap<String, String, S> {
+    fn get(&self, name: &str) -> Option<String> {
+        HashMap::get(self, name).cloned()
+    }
+
+    fn vars_with_prefix(&self, prefix: &str) -> Vec<(String, String)> {
+        let mut vars: Vec<(String, String)> = self
+            .iter()
+            .filter(|(name, _)| name.starts_with(prefix))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        vars.sort();
+        vars
+    }
+}
+
+impl EnvSource for BTreeMap<String, String> {
+    fn get(&self, name: &str) -> Option<String> {
+        BTreeMap::get(self, name).cloned()
+    }
+
+    fn vars_with_prefix(&self, prefix: &str) -> Vec<(String, String)> {
+        self.range(prefix.to_string()..)
+            .take_while(|(name, _)| name.starts_with(prefix))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect()
+    }
+}
+
+/// An in-memory set of variables, for tests or for loading config for a child
+/// process without touching the real environment.
+///
+/// ```
+/// use from_env::{FromEnv, FromEnvTrait, MapEnv};
+///
+/// #[derive(FromEnv)]
+/// struct Config {
+///     host: String,
+///     port: u16,
+/// }
+///
+/// let env = MapEnv::from([("CONFIG_HOST", "localhost"), ("CONFIG_PORT", "8080")]);
+/// let config = Config::from_source(&env).unwrap();
+/// assert_eq!(config.port, 8080);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MapEnv {
+    vars: BTreeMap<String, String>,
+}
+
+impl MapEnv {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets `name` to `value`, replacing any previous value.
+    pub fn set(&mut self, name: impl Into<String>, value: impl Into<String>) -> &mut Self {
+        self.vars.insert(name.into(), value.into());
+        self
+    }
+
+    /// Unsets `name`, returning its previous value.
+    pub fn remove(&mut self, name: &str) -> Option<String> {
+        self.vars.remove(name)
+    }
+}
+
+impl<K: Into<String>, V: Into<String>> FromIterator<(K, V)> for MapEnv {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        MapEnv {
+            vars: iter
+                .into_iter()
+                .map(|(name, value)| (name.into(), value.into()))
+                .collect(),
+        }
+    }
+}
+
+impl<K: Into<String>, V: Into<String>, const N: usize> From<[(K, V); N]> for MapEnv {
+    fn from(pairs: [(K, V); N]) -> Self {
+        pairs.into_iter().collect()
+    }
+}
+
+impl EnvSource for MapEnv {
+    fn get(&self, name: &str) -> Option<String> {
+        self.vars.get(name).cloned()
+    }
+
+    fn vars_with_prefix(&self, prefix: &str) -> Vec<(String, String)> {
+        self.vars.vars_with_prefix(prefix)
+    }
+}
+
+/// A stack of sources checked in order; the first layer that defines a variable
+/// wins.
+///
+/// Add the most specific layer first:
+///
+/// ```no_run
+/// use from_env::{DotEnv, FromEnv, FromEnvTrait, Layered, MapEnv, ProcessEnv};
+///
+/// #[derive(FromEnv)]
+/// struct Config {
+///     host: String,
+///     port: u16,
+/// }
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let overrides = MapEnv::from([("CONFIG_PORT", "9000")]);
+/// let defaults = MapEnv::from([("CONFIG_HOST", "localhost"), ("CONFIG_PORT", "8080")]);
+///
+/// let env = Layered::new()
+///     .layer(overrides)
+///     .layer(ProcessEnv)
+///     .layer(DotEnv::from_path(".env")?)
+///     .layer(DotEnv::from_path("/etc/app/app.env")?)
+///     .layer(defaults);
+///
+/// let config = Config::from_source(&env)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct Layered<'a> {
+    layers: Vec<Box<dyn EnvSource + 'a>>,
+}
+
+impl<'a> Layered<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a layer below all the existing ones, so it is only consulted for
+    /// variables none of them define.
+    pub fn layer(mut self, source: impl EnvSource + 'a) -> Self {
+        self.layers.push(Box::new(source));
+        self
+    }
+
+    fn defining_layer(&self, name: &str) -> Option<&(dyn EnvSource + 'a)> {
+        self.layers
+            .iter()
+            .map(|layer| layer.as_ref())
+            .find(|layer| layer.get(name).is_some())
+    }
+}
+
+impl EnvSource for Layered<'_> {
+    fn get(&self, name: &str) -> Option<String> {
+        self.layers.iter().find_map(|layer| layer.get(name))
+    }
+
+    fn vars_with_prefix(&self, prefix: &str) -> Vec<(String, String)> {
+        let mut vars = BTreeMap::new();
+        for layer in &self.layers {
+            for (name, value) in layer.vars_with_prefix(prefix) {
+                vars.entry(name).or_insert(value);
+            }
+        }
+        vars.into_iter().collect()
+    }
+
+    fn origin(&self, name: &str) -> Option<String> {
+        self.defining_layer(name)?.origin(name)
+    }
+}
@@ -0,0 +1,106 @@
+//! Parsers for the protobuf well-known types prost maps to `prost_types`.
+//!
+//! The `*Value` wrappers need nothing here: prost renders them as `Option<T>`
+//! of the matching scalar.
+
+use crate::ParseError;
+
+const NANOS_PER_SECOND: i128 = 1_000_000_000;
+
+/// The largest magnitude `google.protobuf.Duration` allows, about 10,000 years.
+const MAX_DURATION_SECONDS: i128 = 315_576_000_000;
+
+/// Parses a duration such as `30s`, `1h30m`, `1.5h`, `250ms` or `-2m`.
+///
+/// Units are `ns`, `us` (or `µs`), `ms`, `s`, `m`, `h` and `d`. Components
+/// may be separated by spaces, and a bare `0` is accepted.
+pub fn parse_duration(s: &str) -> Result<prost_types::Duration, ParseError> {
+    let error = |message: &str| ParseError::InvalidDuration {
+        value: s.to_string(),
+        message: message.to_string(),
+    };
+
+    let trimmed = s.trim();
+    let (negative, mut rest) = match trimmed.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, trimmed.strip_prefix('+').unwrap_or(trimmed)),
+    };
+    if rest == "0" {
+        return Ok(prost_types::Duration::default());
+    }
+    if rest.is_empty() {
+        return Err(error("expected a number followed by a unit, e.g. '30s'"));
+    }
+
+    let mut total_nanos: i128 = 0;
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        let (number, after_number) = rest.split_at(number_len);
+        let unit_len = after_number
+            .find(|c: char| c.is_ascii_digit() || c == '.' || c.is_whitespace())
+            .unwrap_or(after_number.len());
+        let (unit, after_unit) = after_number.split_at(unit_len);
+
+        let unit_nanos: i128 = match unit {
+            "ns" => 1,
+            "us" | "µs" => 1_000,
+            "ms" => 1_000_000,
+            "s" => NANOS_PER_SECOND,
+            "m" => 60 * NANOS_PER_SECOND,
+            "h" => 3_600 * NANOS_PER_SECOND,
+            "d" => 86_400 * NANOS_PER_SECOND,
+            "" => return Err(error("missing unit, expected one of ns, us, ms, s, m, h, d")),
+            _ => return Err(error(&format!("unknown unit '{}', expected one of ns, us, ms, s, m, h, d", unit))),
+        };
+
+        let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+        if whole.is_empty() && fraction.is_empty() || fraction.contains('.') {
+            return Err(error(&format!("invalid number '{}'", number)));
+        }
+        let whole: i128 = if whole.is_empty() {
+            0
+        } else {
+            whole.parse().map_err(|_| error("out of range"))?
+        };
+        // Digits past nanosecond precision of the largest unit can't matter
+        let fraction = &fraction[..fraction.len().min(18)];
+        let fraction_nanos = if fraction.is_empty() {
+            0
+        } else {
+            let digits: i128 = fraction.parse().unwrap();
+            digits * unit_nanos / 10i128.pow(fraction.len() as u32)
+        };
+
+        total_nanos = whole
+            .checked_mul(unit_nanos)
+            .and_then(|nanos| nanos.checked_add(fraction_nanos))
+            .and_then(|nanos| total_nanos.checked_add(nanos))
+            .ok_or_else(|| error("out of range"))?;
+        rest = after_unit.trim_start();
+    }
+
+    if total_nanos / NANOS_PER_SECOND > MAX_DURATION_SECONDS {
+        return Err(error("out of range"));
+    }
+    if negative {
+        total_nanos = -total_nanos;
+    }
+
+    Ok(prost_types::Duration {
+        seconds: (total_nanos / NANOS_PER_SECOND) as i64,
+        nanos: (total_nanos % NANOS_PER_SECOND) as i32,
+    })
+}
+
+/// Parses an RFC 3339 timestamp such as `2024-05-01T12:30:00Z` or
+/// `2024-05-01T14:30:00.5+02:00`.
+pub fn parse_timestamp(s: &str) -> Result<prost_types::Timestamp, ParseError> {
+    s.trim()
+        .parse::<prost_types::Timestamp>()
+        .map_err(|e| ParseError::InvalidTimestamp {
+            value: s.to_string(),
+            message: e.to_string(),
+        })
+}
@@ -0,0 +1,93 @@
+use from_env::{FromEnv, FromEnvError, FromEnvTrait, MapEnv};
+
+#[derive(Debug, FromEnv)]
+struct Listen {
+    address: String,
+    port: u16,
+}
+
+#[derive(Debug, FromEnv)]
+struct AppConfig {
+    name: String,
+    workers: u8,
+    debug: Option<bool>,
+    ratios: Vec<f32>,
+    listener: Listen,
+    admin: Option<Listen>,
+}
+
+#[test]
+fn test_all_errors_reports_every_field() {
+    let env = MapEnv::from([
+        ("APPCONFIG_WORKERS", "many"),
+        ("APPCONFIG_DEBUG", "maybe"),
+        ("APPCONFIG_RATIOS", "1.0,x"),
+        ("APPCONFIG_LISTENER_PORT", "99999"),
+        ("APPCONFIG_ADMIN_ADDRESS", "127.0.0.1"),
+    ]);
+
+    let errors = AppConfig::from_source_all_errors(&env).unwrap_err();
+    let vars: Vec<String> = errors
+        .iter()
+        .map(|e| match e {
+            FromEnvError::MissingVariable(var) => var.clone(),
+            FromEnvError::ParseError { var, .. } => var.clone(),
+            FromEnvError::PartiallyConfigured { prefix, .. } => prefix.clone(),
+            other => panic!("Unexpected error {:?}", other),
+        })
+        .collect();
+
+    assert_eq!(
+        vars,
+        vec![
+            "APPCONFIG_NAME",
+            "APPCONFIG_WORKERS",
+            "APPCONFIG_DEBUG",
+            "APPCONFIG_RATIOS",
+            "APPCONFIG_LISTENER_ADDRESS",
+            "APPCONFIG_LISTENER_PORT",
+            "APPCONFIG_ADMIN_",
+        ]
+    );
+}
+
+#[test]
+fn test_all_errors_success() {
+    let env = MapEnv::from([
+        ("APP_APPCONFIG_NAME", "svc"),
+        ("APP_APPCONFIG_WORKERS", "4"),
+        ("APP_APPCONFIG_RATIOS", ""),
+        ("APP_APPCONFIG_LISTENER_ADDRESS", "0.0.0.0"),
+        ("APP_APPCONFIG_LISTENER_PORT", "80"),
+    ]);
+
+    let config = AppConfig::from_source_with_prefix_all_errors(&env, "APP_").unwrap();
+    assert_eq!(config.name, "svc");
+    assert_eq!(config.debug, None);
+    assert!(config.admin.is_none());
+    assert_eq!(config.listener.port, 80);
+}
+
+#[test]
+fn test_all_errors_display() {
+    let errors = Listen::from_source_all_errors(&MapEnv::from([("LISTEN_PORT", "x")])).unwrap_err();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(
+        errors.to_string(),
+        "2 environment variable errors:\n  \
+         - Environment variable 'LISTEN_ADDRESS' not found\n  \
+         - Failed to parse environment variable 'LISTEN_PORT': Failed to parse integer: invalid digit found in string"
+    );
+}
+
+#[test]
+fn test_all_errors_from_process_env() {
+    std::env::set_var("ALLERRORSENV_LISTEN_ADDRESS", "localhost");
+    std::env::remove_var("ALLERRORSENV_LISTEN_PORT");
+
+    let errors = Listen::from_env_with_prefix_all_errors("ALLERRORSENV_").unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert!(errors.to_string().starts_with("1 environment variable error:"));
+
+    std::env::remove_var("ALLERRORSENV_LISTEN_ADDRESS");
+}
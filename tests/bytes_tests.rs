@@ -0,0 +1,109 @@
+use from_env::{BytesEncoding, FromEnv, FromEnvError, FromEnvTrait, MapEnv, ParseError};
+use prost::bytes::Bytes;
+
+#[derive(Debug, FromEnv)]
+struct TlsConfig {
+    key: Vec<u8>,
+    cert: Bytes,
+    #[from_env(encoding = "hex")]
+    fingerprint: Vec<u8>,
+    #[from_env(encoding = "base64url")]
+    token: Option<Vec<u8>>,
+    #[from_env(encoding = "raw")]
+    passphrase: Bytes,
+    pins: Vec<Vec<u8>>,
+}
+
+#[test]
+fn test_bytes_encodings() {
+    let config = TlsConfig::from_pairs([
+        ("TLSCONFIG_KEY", "aGVsbG8="),
+        ("TLSCONFIG_CERT", "aGk"),
+        ("TLSCONFIG_FINGERPRINT", "DEADbeef"),
+        ("TLSCONFIG_TOKEN", "-_8"),
+        ("TLSCONFIG_PASSPHRASE", "p@ss word"),
+        ("TLSCONFIG_PINS", "AQI=, AwQ="),
+    ])
+    .unwrap();
+    assert_eq!(config.key, b"hello");
+    assert_eq!(config.cert, Bytes::from_static(b"hi"));
+    assert_eq!(config.fingerprint, vec![0xde, 0xad, 0xbe, 0xef]);
+    assert_eq!(config.token, Some(vec![0xfb, 0xff]));
+    assert_eq!(config.passphrase, Bytes::from_static(b"p@ss word"));
+    assert_eq!(config.pins, vec![vec![1, 2], vec![3, 4]]);
+}
+
+#[test]
+fn test_bytes_error_reports_offset() {
+    let err = TlsConfig::from_pairs([
+        ("TLSCONFIG_KEY", "aGV*bG8="),
+        ("TLSCONFIG_CERT", ""),
+        ("TLSCONFIG_FINGERPRINT", ""),
+        ("TLSCONFIG_PASSPHRASE", ""),
+        ("TLSCONFIG_PINS", ""),
+    ])
+    .unwrap_err();
+    match &err {
+        FromEnvError::ParseError {
+            var,
+            source: ParseError::InvalidBytes { encoding, offset, .. },
+        } => {
+            assert_eq!(var, "TLSCONFIG_KEY");
+            assert_eq!(*encoding, BytesEncoding::Base64);
+            assert_eq!(*offset, 3);
+        }
+        other => panic!("Expected InvalidBytes, got {:?}", other),
+    }
+    assert_eq!(
+        err.to_string(),
+        "Failed to parse environment variable 'TLSCONFIG_KEY': Invalid base64 at byte 3: unexpected character '*'"
+    );
+}
+
+#[test]
+fn test_parse_bytes() {
+    assert_eq!(from_env::parse_bytes("  Zm9vYg==\n", BytesEncoding::Base64).unwrap(), b"foob");
+    assert_eq!(from_env::parse_bytes("Zm9vYg", BytesEncoding::Base64).unwrap(), b"foob");
+    assert_eq!(from_env::parse_bytes("", BytesEncoding::Hex).unwrap(), b"");
+
+    let offset = |s, encoding| match from_env::parse_bytes(s, encoding) {
+        Err(ParseError::InvalidBytes { offset, .. }) => offset,
+        other => panic!("Expected InvalidBytes, got {:?}", other),
+    };
+    assert_eq!(offset(" abc", BytesEncoding::Hex), 3);
+    assert_eq!(offset("ab0g", BytesEncoding::Hex), 3);
+    assert_eq!(offset("-_8", BytesEncoding::Base64), 0);
+    assert_eq!(offset("Zm9vY", BytesEncoding::Base64), 4);
+    assert_eq!(offset("Zm9=v", BytesEncoding::Base64), 3);
+}
+
+#[derive(Debug, FromEnv)]
+#[from_env(proto3_defaults)]
+struct ProtoBlob {
+    data: Vec<u8>,
+    extra: Bytes,
+}
+
+#[test]
+fn test_bytes_proto3_default() {
+    let config = ProtoBlob::from_source(&MapEnv::new()).unwrap();
+    assert!(config.data.is_empty());
+    assert!(config.extra.is_empty());
+}
+
+// Spelled the way prost-build generates `bytes` fields
+#[derive(Clone, PartialEq, prost::Message, FromEnv)]
+pub struct ProstKey {
+    #[prost(bytes = "vec", tag = "1")]
+    pub key: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    #[from_env(encoding = "hex")]
+    pub fingerprint: ::prost::alloc::vec::Vec<u8>,
+}
+
+#[test]
+fn test_bytes_prost_path() {
+    let config = ProstKey::from_pairs([("PROSTKEY_KEY", "aGVsbG8="), ("PROSTKEY_FINGERPRINT", "DEADbeef")]).unwrap();
+    assert_eq!(config.key, b"hello");
+    assert_eq!(config.fingerprint, vec![0xde, 0xad, 0xbe, 0xef]);
+}
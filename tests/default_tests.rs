@@ -0,0 +1,172 @@
+use from_env::{FromEnv, FromEnvError, FromEnvTrait, MapEnv, ParseError};
+
+fn default_workers() -> usize {
+    4
+}
+
+mod defaults {
+    pub fn bind() -> std::net::Ipv4Addr {
+        std::net::Ipv4Addr::LOCALHOST
+    }
+}
+
+#[derive(Debug, FromEnv)]
+struct ServerConfig {
+    host: String,
+    #[from_env(default = "8080")]
+    port: u16,
+    #[from_env(default = "a, b")]
+    tags: Vec<String>,
+    #[from_env(default_fn = "default_workers")]
+    workers: usize,
+    #[from_env(default_fn = "defaults::bind")]
+    bind: std::net::Ipv4Addr,
+}
+
+#[test]
+fn test_field_defaults_apply_when_missing() {
+    let config = ServerConfig::from_pairs([("SERVERCONFIG_HOST", "example.com")]).unwrap();
+    assert_eq!(config.port, 8080);
+    assert_eq!(config.tags, vec!["a", "b"]);
+    assert_eq!(config.workers, 4);
+    assert_eq!(config.bind, std::net::Ipv4Addr::LOCALHOST);
+}
+
+#[test]
+fn test_field_defaults_overridden_by_variables() {
+    let config = ServerConfig::from_pairs([
+        ("SERVERCONFIG_HOST", "example.com"),
+        ("SERVERCONFIG_PORT", "9000"),
+        ("SERVERCONFIG_TAGS", "x"),
+        ("SERVERCONFIG_WORKERS", "16"),
+        ("SERVERCONFIG_BIND", "10.0.0.1"),
+    ])
+    .unwrap();
+    assert_eq!(config.port, 9000);
+    assert_eq!(config.tags, vec!["x"]);
+    assert_eq!(config.workers, 16);
+    assert_eq!(config.bind, std::net::Ipv4Addr::new(10, 0, 0, 1));
+}
+
+#[derive(Debug, FromEnv)]
+struct BadDefault {
+    #[from_env(default = "eighty")]
+    port: u16,
+}
+
+#[test]
+fn test_default_is_parsed_like_a_real_value() {
+    match BadDefault::from_source(&MapEnv::new()) {
+        Err(FromEnvError::ParseError {
+            var,
+            source: ParseError::ParseInt(_),
+        }) => assert_eq!(var, "BADDEFAULT_PORT"),
+        other => panic!("Expected ParseError, got {:?}", other),
+    }
+}
+
+// Struct-level default, as for a prost message
+#[derive(Debug, Default, FromEnv)]
+#[from_env(default)]
+struct DatabaseConfig {
+    host: String,
+    port: u32,
+    ssl_enabled: bool,
+    #[from_env(default = "10")]
+    max_connections: u32,
+}
+
+#[test]
+fn test_struct_default_fills_missing_fields() {
+    let config = DatabaseConfig::from_pairs([("DATABASECONFIG_HOST", "db")]).unwrap();
+    assert_eq!(config.host, "db");
+    assert_eq!(config.port, 0);
+    assert!(!config.ssl_enabled);
+    assert_eq!(config.max_connections, 10);
+
+    let errors =
+        DatabaseConfig::from_source_all_errors(&MapEnv::from([("DATABASECONFIG_PORT", "x")])).unwrap_err();
+    assert_eq!(errors.len(), 1);
+}
+
+#[test]
+fn test_struct_default_load_keeps_existing_values() {
+    let mut config = DatabaseConfig {
+        host: "old".to_string(),
+        port: 5432,
+        ssl_enabled: true,
+        max_connections: 1,
+    };
+    config
+        .load_from_source(&MapEnv::from([("DATABASECONFIG_HOST", "new")]))
+        .unwrap();
+    assert_eq!(config.host, "new");
+    assert_eq!(config.port, 5432);
+    assert!(config.ssl_enabled);
+    // Field-level defaults still apply
+    assert_eq!(config.max_connections, 10);
+}
+
+#[derive(Debug, FromEnv)]
+struct Listen {
+    address: String,
+    #[from_env(default = "80")]
+    port: u16,
+}
+
+#[derive(Debug, FromEnv)]
+struct ProxyConfig {
+    upstream: Option<Listen>,
+}
+
+#[test]
+fn test_defaulted_fields_do_not_make_optional_nested_present() {
+    let config = ProxyConfig::from_source(&MapEnv::new()).unwrap();
+    assert!(config.upstream.is_none());
+
+    let config = ProxyConfig::from_pairs([("PROXYCONFIG_UPSTREAM_ADDRESS", "backend")]).unwrap();
+    let upstream = config.upstream.unwrap();
+    assert_eq!(upstream.address, "backend");
+    assert_eq!(upstream.port, 80);
+}
+
+// Mirrors `DatabaseConfig` from examples/config.proto, plus a repeated field
+// and a message field, as prost would render them
+#[derive(Debug, FromEnv)]
+#[from_env(proto3_defaults)]
+struct ProtoDatabaseConfig {
+    host: ::prost::alloc::string::String,
+    port: u32,
+    database: ::prost::alloc::string::String,
+    ssl_enabled: bool,
+    max_connections: u32,
+    replicas: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    primary: ::core::option::Option<Listen>,
+}
+
+#[test]
+fn test_proto3_defaults_zero_values() {
+    let config = ProtoDatabaseConfig::from_pairs([("PROTODATABASECONFIG_HOST", "db.internal")]).unwrap();
+    assert_eq!(config.host, "db.internal");
+    assert_eq!(config.port, 0);
+    assert_eq!(config.database, "");
+    assert!(!config.ssl_enabled);
+    assert_eq!(config.max_connections, 0);
+    assert!(config.replicas.is_empty());
+    assert!(config.primary.is_none());
+}
+
+#[test]
+fn test_proto3_defaults_still_parse_set_values() {
+    let config = ProtoDatabaseConfig::from_pairs([
+        ("PROTODATABASECONFIG_PORT", "5432"),
+        ("PROTODATABASECONFIG_REPLICAS", "r1,r2"),
+        ("PROTODATABASECONFIG_PRIMARY_ADDRESS", "primary"),
+    ])
+    .unwrap();
+    assert_eq!(config.port, 5432);
+    assert_eq!(config.replicas, vec!["r1", "r2"]);
+    assert_eq!(config.primary.unwrap().port, 80);
+
+    assert!(ProtoDatabaseConfig::from_pairs([("PROTODATABASECONFIG_PORT", "-1")]).is_err());
+}
@@ -0,0 +1,133 @@
+use from_env::{DotEnv, DotEnvError, FromEnv, FromEnvError, FromEnvTrait, ParseError};
+
+#[test]
+fn test_dotenv_syntax() {
+    let env = DotEnv::parse(
+        r#"
+# full-line comment
+PLAIN=value
+export EXPORTED=yes
+  INDENTED = spaced value   # trailing comment
+HASH=a#b
+EMPTY=
+SINGLE='literal \n $HOME'
+DOUBLE="tab\tquote\"backslash\\dollar\$"
+MULTI="line one
+line two"
+SINGLE_MULTI='a
+b' # comment after quote
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(env.get("PLAIN"), Some("value"));
+    assert_eq!(env.get("EXPORTED"), Some("yes"));
+    assert_eq!(env.get("INDENTED"), Some("spaced value"));
+    assert_eq!(env.get("HASH"), Some("a#b"));
+    assert_eq!(env.get("EMPTY"), Some(""));
+    assert_eq!(env.get("SINGLE"), Some("literal \\n $HOME"));
+    assert_eq!(env.get("DOUBLE"), Some("tab\tquote\"backslash\\dollar$"));
+    assert_eq!(env.get("MULTI"), Some("line one\nline two"));
+    assert_eq!(env.get("SINGLE_MULTI"), Some("a\nb"));
+    assert_eq!(env.line("PLAIN"), Some(3));
+    assert_eq!(env.line("MULTI"), Some(10));
+}
+
+#[test]
+fn test_dotenv_last_definition_wins() {
+    let env = DotEnv::parse("A=1\nA=2\n").unwrap();
+    assert_eq!(env.get("A"), Some("2"));
+    assert_eq!(env.line("A"), Some(2));
+}
+
+fn syntax_error(content: &str) -> (usize, usize, String) {
+    match DotEnv::parse(content) {
+        Err(DotEnvError::Syntax {
+            line,
+            column,
+            message,
+            ..
+        }) => (line, column, message),
+        other => panic!("Expected syntax error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_dotenv_error_locations() {
+    assert_eq!(syntax_error("A=1\nB 2\n").0, 2);
+    assert_eq!(syntax_error("A=1\nB 2\n").1, 3);
+
+    let (line, column, message) = syntax_error("A=1\nB=\"open\nstill open\n");
+    assert_eq!((line, column), (2, 3));
+    assert!(message.contains("unterminated"));
+
+    let (line, column, message) = syntax_error("A=\"bad \\q escape\"");
+    assert_eq!((line, column), (1, 8));
+    assert!(message.contains("\\q"));
+
+    let (line, column, _) = syntax_error("A='x' trailing");
+    assert_eq!((line, column), (1, 7));
+
+    let (line, column, _) = syntax_error("1A=x");
+    assert_eq!((line, column), (1, 1));
+}
+
+#[test]
+fn test_dotenv_error_display() {
+    let err = DotEnv::parse("A=1\nB 2\n").unwrap_err();
+    assert_eq!(err.to_string(), "<string>:2:3: expected '=' after key 'B'");
+}
+
+#[derive(Debug, FromEnv)]
+struct FileConfig {
+    host: String,
+    port: u16,
+}
+
+fn write_temp(name: &str, content: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("from-env-{}-{}.env", name, std::process::id()));
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+#[test]
+fn test_load_from_dotenv_file() {
+    let path = write_temp("ok", "FILECONFIG_HOST=localhost\nFILECONFIG_PORT=8080\n");
+
+    let env = DotEnv::from_path(&path).unwrap();
+    let config = FileConfig::from_source(&env).unwrap();
+    assert_eq!(config.host, "localhost");
+    assert_eq!(config.port, 8080);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_parse_error_reports_file_and_line() {
+    let path = write_temp("bad", "FILECONFIG_HOST=localhost\n\nFILECONFIG_PORT=eighty\n");
+
+    let env = DotEnv::from_path(&path).unwrap();
+    let err = FileConfig::from_source(&env).unwrap_err();
+    match &err {
+        FromEnvError::Located { origin, source } => {
+            assert_eq!(origin, &format!("{}:3", path.display()));
+            assert!(matches!(
+                **source,
+                FromEnvError::ParseError {
+                    source: ParseError::ParseInt(_),
+                    ..
+                }
+            ));
+        }
+        other => panic!("Expected Located, got {:?}", other),
+    }
+    assert!(err.to_string().starts_with(&format!("{}:3: Failed to parse", path.display())));
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_dotenv_missing_file() {
+    let err = DotEnv::from_path("/nonexistent/from-env/.env").unwrap_err();
+    assert!(matches!(err, DotEnvError::Io { .. }));
+}
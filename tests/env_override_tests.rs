@@ -0,0 +1,145 @@
+use from_env::{FromEnv, FromEnvError, FromEnvTrait, MapEnv};
+use std::sync::Mutex;
+
+#[derive(Debug, FromEnv)]
+#[from_env(prefix = "APP_")]
+struct WebConfig {
+    host: String,
+    #[from_env(env = "PORT")]
+    port: u16,
+    #[from_env(env = "DATABASE_URL")]
+    database_url: Option<String>,
+}
+
+#[test]
+fn test_env_override_ignores_struct_prefix() {
+    let env = MapEnv::from([
+        ("APP_WEBCONFIG_HOST", "0.0.0.0"),
+        ("PORT", "8080"),
+        ("DATABASE_URL", "postgres://localhost/app"),
+        // The generated name is not consulted
+        ("APP_WEBCONFIG_PORT", "1"),
+    ]);
+
+    let config = WebConfig::from_source(&env).unwrap();
+    assert_eq!(config.host, "0.0.0.0");
+    assert_eq!(config.port, 8080);
+    assert_eq!(config.database_url.as_deref(), Some("postgres://localhost/app"));
+}
+
+#[test]
+fn test_env_override_ignores_runtime_prefix() {
+    let env = MapEnv::from([("TENANT_APP_WEBCONFIG_HOST", "tenant.example.com"), ("PORT", "3000")]);
+
+    let config = WebConfig::from_source_with_prefix(&env, "TENANT_").unwrap();
+    assert_eq!(config.host, "tenant.example.com");
+    assert_eq!(config.port, 3000);
+    assert_eq!(config.database_url, None);
+}
+
+#[test]
+fn test_env_override_missing_variable_name() {
+    let env = MapEnv::from([("APP_WEBCONFIG_HOST", "localhost")]);
+
+    match WebConfig::from_source(&env) {
+        Err(FromEnvError::MissingVariable(var)) => assert_eq!(var, "PORT"),
+        other => panic!("Expected MissingVariable, got {:?}", other),
+    }
+}
+
+#[derive(Debug, FromEnv)]
+struct Tls {
+    cert: String,
+}
+
+#[derive(Debug, FromEnv)]
+struct ProxyConfig {
+    #[from_env(env = "RUST_LOG")]
+    log: String,
+    #[from_env(env = "TLS")]
+    tls: Tls,
+}
+
+#[test]
+fn test_env_override_on_nested_struct() {
+    let env = MapEnv::from([("RUST_LOG", "info"), ("TLS_CERT", "/etc/cert.pem")]);
+
+    let config = ProxyConfig::from_source(&env).unwrap();
+    assert_eq!(config.log, "info");
+    assert_eq!(config.tls.cert, "/etc/cert.pem");
+}
+
+#[derive(Debug, FromEnv)]
+struct MigratingConfig {
+    #[from_env(env = "PORT", aliases = ["HTTP_PORT", "LEGACY_PORT"])]
+    port: u16,
+    #[from_env(deprecated = "OLD_MIGRATINGCONFIG_TIMEOUT")]
+    timeout: Option<u32>,
+}
+
+#[test]
+fn test_aliases_tried_in_order() {
+    let env = MapEnv::from([("HTTP_PORT", "81"), ("LEGACY_PORT", "82")]);
+    assert_eq!(MigratingConfig::from_source(&env).unwrap().port, 81);
+
+    let env = MapEnv::from([("LEGACY_PORT", "82")]);
+    assert_eq!(MigratingConfig::from_source(&env).unwrap().port, 82);
+
+    let env = MapEnv::from([("PORT", "80"), ("HTTP_PORT", "81")]);
+    assert_eq!(MigratingConfig::from_source(&env).unwrap().port, 80);
+}
+
+#[test]
+fn test_aliases_missing_reports_primary_name() {
+    match MigratingConfig::from_source(&MapEnv::new()) {
+        Err(FromEnvError::MissingVariable(var)) => assert_eq!(var, "PORT"),
+        other => panic!("Expected MissingVariable, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_deprecated_name_is_honoured_and_reported() {
+    static SEEN: Mutex<Vec<String>> = Mutex::new(Vec::new());
+    from_env::set_deprecation_hook(|d| SEEN.lock().unwrap().push(d.to_string()));
+
+    let env = MapEnv::from([("PORT", "80"), ("OLD_MIGRATINGCONFIG_TIMEOUT", "30")]);
+    let config = MigratingConfig::from_source(&env).unwrap();
+    assert_eq!(config.timeout, Some(30));
+    assert!(SEEN.lock().unwrap().contains(
+        &"Environment variable 'OLD_MIGRATINGCONFIG_TIMEOUT' is deprecated, use 'MIGRATINGCONFIG_TIMEOUT' instead"
+            .to_string()
+    ));
+
+    // The current name wins over the deprecated one
+    let env = MapEnv::from([
+        ("PORT", "80"),
+        ("MIGRATINGCONFIG_TIMEOUT", "5"),
+        ("OLD_MIGRATINGCONFIG_TIMEOUT", "30"),
+    ]);
+    assert_eq!(MigratingConfig::from_source(&env).unwrap().timeout, Some(5));
+}
+
+#[derive(Debug, FromEnv)]
+struct Upstream {
+    #[from_env(aliases = ["UPSTREAM_HOSTNAME"])]
+    host: String,
+    port: u16,
+}
+
+#[derive(Debug, FromEnv)]
+struct GatewayConfig {
+    upstream: Option<Upstream>,
+}
+
+#[test]
+fn test_aliases_count_towards_optional_nested_presence() {
+    let env = MapEnv::from([
+        ("GATEWAYCONFIG_UPSTREAM_PORT", "80"),
+        ("UPSTREAM_HOSTNAME", "backend"),
+    ]);
+
+    let config = GatewayConfig::from_source(&env).unwrap();
+    let upstream = config.upstream.unwrap();
+    assert_eq!(upstream.host, "backend");
+    assert_eq!(upstream.port, 80);
+}
@@ -0,0 +1,171 @@
+use from_env::{DotEnv, EnvSource, FromEnv, FromEnvError, FromEnvTrait, Layered, MapEnv};
+use std::collections::HashMap;
+
+#[derive(Debug, FromEnv, PartialEq)]
+struct Listen {
+    address: String,
+    port: u32,
+}
+
+#[derive(Debug, FromEnv, PartialEq)]
+struct SourceConfig {
+    name: String,
+    workers: Option<u8>,
+    tags: Vec<String>,
+    listener: Listen,
+}
+
+fn map(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_from_source_hash_map() {
+    let source = map(&[
+        ("SOURCECONFIG_NAME", "worker"),
+        ("SOURCECONFIG_TAGS", "a,b"),
+        ("SOURCECONFIG_LISTENER_ADDRESS", "0.0.0.0"),
+        ("SOURCECONFIG_LISTENER_PORT", "8080"),
+    ]);
+
+    let config = SourceConfig::from_source(&source).unwrap();
+    assert_eq!(config.name, "worker");
+    assert_eq!(config.workers, None);
+    assert_eq!(config.tags, vec!["a", "b"]);
+    assert_eq!(config.listener.port, 8080);
+}
+
+#[test]
+fn test_from_source_with_prefix() {
+    let source = map(&[
+        ("CHILD_SOURCECONFIG_NAME", "child"),
+        ("CHILD_SOURCECONFIG_WORKERS", "4"),
+        ("CHILD_SOURCECONFIG_TAGS", ""),
+        ("CHILD_SOURCECONFIG_LISTENER_ADDRESS", "127.0.0.1"),
+        ("CHILD_SOURCECONFIG_LISTENER_PORT", "9000"),
+    ]);
+
+    let config = SourceConfig::from_source_with_prefix(&source, "CHILD_").unwrap();
+    assert_eq!(config.name, "child");
+    assert_eq!(config.workers, Some(4));
+    assert!(config.tags.is_empty());
+    assert_eq!(config.listener.address, "127.0.0.1");
+}
+
+#[test]
+fn test_load_from_source_missing_variable() {
+    let source = map(&[("LISTEN_ADDRESS", "localhost")]);
+
+    let mut listen = Listen {
+        address: "old".to_string(),
+        port: 1,
+    };
+    match listen.load_from_source(&source) {
+        Err(FromEnvError::MissingVariable(var)) => assert_eq!(var, "LISTEN_PORT"),
+        other => panic!("Expected MissingVariable, got {:?}", other),
+    }
+}
+
+// A custom source: every variable maps to the same value
+struct Constant(&'static str);
+
+impl EnvSource for Constant {
+    fn get(&self, _name: &str) -> Option<String> {
+        Some(self.0.to_string())
+    }
+
+    fn vars_with_prefix(&self, _prefix: &str) -> Vec<(String, String)> {
+        Vec::new()
+    }
+}
+
+#[test]
+fn test_from_custom_source() {
+    let listen = Listen::from_source(&Constant("42")).unwrap();
+    assert_eq!(listen.address, "42");
+    assert_eq!(listen.port, 42);
+}
+
+#[test]
+fn test_hash_map_vars_with_prefix() {
+    let source = map(&[("APP_B", "2"), ("APP_A", "1"), ("OTHER", "3")]);
+
+    assert_eq!(
+        source.vars_with_prefix("APP_"),
+        vec![
+            ("APP_A".to_string(), "1".to_string()),
+            ("APP_B".to_string(), "2".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_from_pairs() {
+    let listen = Listen::from_pairs([("LISTEN_ADDRESS", "::1"), ("LISTEN_PORT", "443")]).unwrap();
+    assert_eq!(
+        listen,
+        Listen {
+            address: "::1".to_string(),
+            port: 443,
+        }
+    );
+}
+
+#[test]
+fn test_map_env_ignores_process_env() {
+    std::env::set_var("LISTEN_PORT", "1");
+
+    let mut env = MapEnv::new();
+    env.set("LISTEN_ADDRESS", "localhost").set("LISTEN_PORT", "80");
+    let listen = Listen::from_source(&env).unwrap();
+    assert_eq!(listen.port, 80);
+
+    env.remove("LISTEN_PORT");
+    assert!(matches!(
+        Listen::from_source(&env),
+        Err(FromEnvError::MissingVariable(_))
+    ));
+
+    std::env::remove_var("LISTEN_PORT");
+}
+
+#[test]
+fn test_layered_first_layer_wins() {
+    let overrides = MapEnv::from([("LISTEN_PORT", "9000")]);
+    let file = DotEnv::parse("LISTEN_ADDRESS=file.example.com\nLISTEN_PORT=7000\n").unwrap();
+    let defaults = MapEnv::from([("LISTEN_ADDRESS", "localhost"), ("LISTEN_PORT", "8080")]);
+
+    let env = Layered::new().layer(overrides).layer(&file).layer(defaults);
+    let listen = Listen::from_source(&env).unwrap();
+    assert_eq!(listen.address, "file.example.com");
+    assert_eq!(listen.port, 9000);
+
+    let env = Layered::new()
+        .layer(MapEnv::from([("LISTEN_ADDRESS", "x")]))
+        .layer(MapEnv::from([("LISTEN_ADDRESS", "y"), ("LISTEN_PORT", "1")]));
+    assert_eq!(
+        env.vars_with_prefix("LISTEN_"),
+        vec![
+            ("LISTEN_ADDRESS".to_string(), "x".to_string()),
+            ("LISTEN_PORT".to_string(), "1".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_layered_origin_comes_from_defining_layer() {
+    let file = DotEnv::parse("LISTEN_ADDRESS=localhost\nLISTEN_PORT=nope\n").unwrap();
+    let env = Layered::new()
+        .layer(MapEnv::from([("LISTEN_ADDRESS", "override")]))
+        .layer(file);
+
+    assert_eq!(env.origin("LISTEN_ADDRESS"), None);
+    assert_eq!(env.origin("LISTEN_PORT"), Some("<string>:2".to_string()));
+    assert!(matches!(
+        Listen::from_source(&env),
+        Err(FromEnvError::Located { .. })
+    ));
+}
@@ -0,0 +1,123 @@
+use from_env::{FromEnv, FromEnvError, FromEnvTrait, MapEnv};
+
+#[derive(Debug, FromEnv, PartialEq)]
+struct TlsSettings {
+    cert_path: String,
+    key_path: String,
+}
+
+#[derive(Debug, FromEnv, PartialEq)]
+#[from_env(prefix = "APP_")]
+struct HttpConfig {
+    port: u16,
+    #[from_env(flatten)]
+    tls: TlsSettings,
+}
+
+#[derive(Debug, FromEnv, PartialEq)]
+#[from_env(prefix = "APP_")]
+struct GrpcConfig {
+    port: u16,
+    #[from_env(flatten)]
+    tls: Option<TlsSettings>,
+}
+
+#[test]
+fn test_flatten_required() {
+    let config = HttpConfig::from_pairs([
+        ("APP_HTTPCONFIG_PORT", "8080"),
+        ("APP_HTTPCONFIG_CERT_PATH", "/etc/tls/cert.pem"),
+        ("APP_HTTPCONFIG_KEY_PATH", "/etc/tls/key.pem"),
+    ])
+    .unwrap();
+    assert_eq!(
+        config,
+        HttpConfig {
+            port: 8080,
+            tls: TlsSettings {
+                cert_path: "/etc/tls/cert.pem".to_string(),
+                key_path: "/etc/tls/key.pem".to_string(),
+            },
+        }
+    );
+
+    // No `_TLS_` segment
+    let result = HttpConfig::from_pairs([
+        ("APP_HTTPCONFIG_PORT", "8080"),
+        ("APP_HTTPCONFIG_TLS_CERT_PATH", "/etc/tls/cert.pem"),
+        ("APP_HTTPCONFIG_TLS_KEY_PATH", "/etc/tls/key.pem"),
+    ]);
+    assert!(matches!(result, Err(FromEnvError::MissingVariable(var)) if var == "APP_HTTPCONFIG_CERT_PATH"));
+}
+
+#[test]
+fn test_flatten_optional() {
+    let config = GrpcConfig::from_pairs([("APP_GRPCCONFIG_PORT", "9090")]).unwrap();
+    assert_eq!(config.tls, None);
+
+    let config = GrpcConfig::from_pairs([
+        ("APP_GRPCCONFIG_PORT", "9090"),
+        ("APP_GRPCCONFIG_CERT_PATH", "cert.pem"),
+        ("APP_GRPCCONFIG_KEY_PATH", "key.pem"),
+    ])
+    .unwrap();
+    assert_eq!(
+        config.tls,
+        Some(TlsSettings {
+            cert_path: "cert.pem".to_string(),
+            key_path: "key.pem".to_string(),
+        })
+    );
+
+    let result = GrpcConfig::from_pairs([("APP_GRPCCONFIG_PORT", "9090"), ("APP_GRPCCONFIG_CERT_PATH", "cert.pem")]);
+    assert!(matches!(
+        result,
+        Err(FromEnvError::PartiallyConfigured { missing, .. }) if missing == ["APP_GRPCCONFIG_KEY_PATH"]
+    ));
+}
+
+#[derive(Debug, FromEnv, PartialEq)]
+struct Listener {
+    host: String,
+    #[from_env(flatten)]
+    tls: TlsSettings,
+}
+
+#[derive(Debug, FromEnv, PartialEq)]
+struct Server {
+    public: Listener,
+    admin: Listener,
+}
+
+#[test]
+fn test_flatten_inside_nested() {
+    // The same group is reused under each listener's own prefix
+    let config = Server::from_pairs([
+        ("SERVER_PUBLIC_HOST", "0.0.0.0"),
+        ("SERVER_PUBLIC_CERT_PATH", "public.pem"),
+        ("SERVER_PUBLIC_KEY_PATH", "public.key"),
+        ("SERVER_ADMIN_HOST", "127.0.0.1"),
+        ("SERVER_ADMIN_CERT_PATH", "admin.pem"),
+        ("SERVER_ADMIN_KEY_PATH", "admin.key"),
+    ])
+    .unwrap();
+    assert_eq!(config.public.tls.cert_path, "public.pem");
+    assert_eq!(config.admin.tls.key_path, "admin.key");
+}
+
+#[test]
+fn test_flatten_all_errors() {
+    let env = MapEnv::from([("APP_HTTPCONFIG_PORT", "nope")]);
+
+    let errors = HttpConfig::from_source_all_errors(&env).unwrap_err();
+    let vars: Vec<String> = errors
+        .errors()
+        .iter()
+        .map(|e| match e {
+            FromEnvError::MissingVariable(var) => var.clone(),
+            FromEnvError::ParseError { var, .. } => var.clone(),
+            other => panic!("unexpected error {:?}", other),
+        })
+        .collect();
+    assert_eq!(vars, ["APP_HTTPCONFIG_PORT", "APP_HTTPCONFIG_CERT_PATH", "APP_HTTPCONFIG_KEY_PATH"]);
+}
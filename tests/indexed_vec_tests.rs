@@ -0,0 +1,146 @@
+use from_env::{FromEnv, FromEnvError, FromEnvTrait, MapEnv};
+
+#[derive(Debug, PartialEq, FromEnv)]
+struct UpstreamConfig {
+    host: String,
+    port: Option<u16>,
+}
+
+#[derive(Debug, FromEnv)]
+struct AppConfig {
+    name: String,
+    upstreams: Vec<UpstreamConfig>,
+}
+
+fn upstream(host: &str, port: Option<u16>) -> UpstreamConfig {
+    UpstreamConfig {
+        host: host.to_string(),
+        port,
+    }
+}
+
+#[test]
+fn test_indexed_vec() {
+    let config = AppConfig::from_pairs([
+        ("APPCONFIG_NAME", "gateway"),
+        ("APPCONFIG_UPSTREAMS_0_HOST", "a.internal"),
+        ("APPCONFIG_UPSTREAMS_0_PORT", "8080"),
+        ("APPCONFIG_UPSTREAMS_1_HOST", "b.internal"),
+        ("APPCONFIG_UPSTREAMS_10_HOST", "k.internal"),
+        ("APPCONFIG_UPSTREAMS_2_HOST", "c.internal"),
+        ("APPCONFIG_UPSTREAMS_3_HOST", "d.internal"),
+        ("APPCONFIG_UPSTREAMS_4_HOST", "e.internal"),
+        ("APPCONFIG_UPSTREAMS_5_HOST", "f.internal"),
+        ("APPCONFIG_UPSTREAMS_6_HOST", "g.internal"),
+        ("APPCONFIG_UPSTREAMS_7_HOST", "h.internal"),
+        ("APPCONFIG_UPSTREAMS_8_HOST", "i.internal"),
+        ("APPCONFIG_UPSTREAMS_9_HOST", "j.internal"),
+    ])
+    .unwrap();
+    assert_eq!(config.upstreams.len(), 11);
+    assert_eq!(config.upstreams[0], upstream("a.internal", Some(8080)));
+    assert_eq!(config.upstreams[1], upstream("b.internal", None));
+    assert_eq!(config.upstreams[10], upstream("k.internal", None));
+
+    let config = AppConfig::from_pairs([("APPCONFIG_NAME", "gateway")]).unwrap();
+    assert!(config.upstreams.is_empty());
+}
+
+#[test]
+fn test_indexed_vec_count() {
+    let mut env = MapEnv::from([
+        ("APPCONFIG_NAME", "gateway"),
+        ("APPCONFIG_UPSTREAMS_COUNT", "2"),
+        ("APPCONFIG_UPSTREAMS_0_HOST", "a.internal"),
+        ("APPCONFIG_UPSTREAMS_1_HOST", "b.internal"),
+    ]);
+    let config = AppConfig::from_source(&env).unwrap();
+    assert_eq!(config.upstreams.len(), 2);
+
+    env.set("APPCONFIG_UPSTREAMS_COUNT", "3");
+    match AppConfig::from_source(&env) {
+        Err(FromEnvError::MissingVariable(var)) => assert_eq!(var, "APPCONFIG_UPSTREAMS_2_HOST"),
+        other => panic!("Expected MissingVariable, got {:?}", other),
+    }
+
+    env.set("APPCONFIG_UPSTREAMS_COUNT", "1");
+    assert_eq!(
+        AppConfig::from_source(&env).unwrap_err().to_string(),
+        "'APPCONFIG_UPSTREAMS_' sets index 1, but its count is 1"
+    );
+}
+
+#[test]
+fn test_indexed_vec_gap_and_duplicate() {
+    let err = AppConfig::from_pairs([
+        ("APPCONFIG_NAME", "gateway"),
+        ("APPCONFIG_UPSTREAMS_0_HOST", "a.internal"),
+        ("APPCONFIG_UPSTREAMS_2_HOST", "c.internal"),
+    ])
+    .unwrap_err();
+    assert!(matches!(err, FromEnvError::IndexGap { missing: 1, next: 2, .. }));
+    assert_eq!(
+        err.to_string(),
+        "'APPCONFIG_UPSTREAMS_' sets index 2 but not index 1; indexes must start at 0 with no gaps"
+    );
+
+    let err = AppConfig::from_pairs([
+        ("APPCONFIG_NAME", "gateway"),
+        ("APPCONFIG_UPSTREAMS_0_HOST", "a.internal"),
+        ("APPCONFIG_UPSTREAMS_00_PORT", "80"),
+    ])
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "'APPCONFIG_UPSTREAMS_' index 0 is set more than once, as \
+         [APPCONFIG_UPSTREAMS_0_, APPCONFIG_UPSTREAMS_00_]"
+    );
+}
+
+#[test]
+fn test_indexed_vec_all_errors() {
+    let env = MapEnv::from([
+        ("APPCONFIG_UPSTREAMS_0_PORT", "x"),
+        ("APPCONFIG_UPSTREAMS_1_HOST", "b.internal"),
+        ("APPCONFIG_UPSTREAMS_1_PORT", "99999"),
+    ]);
+    let errors = AppConfig::from_source_all_errors(&env).unwrap_err();
+    let vars: Vec<String> = errors
+        .iter()
+        .map(|e| match e {
+            FromEnvError::MissingVariable(var) => var.clone(),
+            FromEnvError::ParseError { var, .. } => var.clone(),
+            other => panic!("Unexpected error {:?}", other),
+        })
+        .collect();
+    assert_eq!(
+        vars,
+        vec![
+            "APPCONFIG_NAME",
+            "APPCONFIG_UPSTREAMS_0_HOST",
+            "APPCONFIG_UPSTREAMS_0_PORT",
+            "APPCONFIG_UPSTREAMS_1_PORT",
+        ]
+    );
+}
+
+#[derive(Debug, FromEnv)]
+struct Mesh {
+    gateway: Option<AppConfig>,
+}
+
+#[test]
+fn test_indexed_vec_counts_towards_optional_presence() {
+    let config = Mesh::from_pairs([
+        ("MESH_GATEWAY_NAME", "edge"),
+        ("MESH_GATEWAY_UPSTREAMS_0_HOST", "a.internal"),
+    ])
+    .unwrap();
+    assert_eq!(config.gateway.unwrap().upstreams.len(), 1);
+
+    // An element alone marks the group as present, so the missing name is reported
+    match Mesh::from_pairs([("MESH_GATEWAY_UPSTREAMS_0_HOST", "a.internal")]) {
+        Err(FromEnvError::PartiallyConfigured { missing, .. }) => assert_eq!(missing, vec!["MESH_GATEWAY_NAME"]),
+        other => panic!("Expected PartiallyConfigured, got {:?}", other),
+    }
+}
@@ -1,4 +1,6 @@
-use from_env::{FromEnv, FromEnvTrait};
+#![allow(clippy::bool_assert_comparison, clippy::approx_constant)]
+
+use from_env::{FromEnv, FromEnvError, FromEnvTrait, ParseError};
 
 // Test 1: Simple struct from env
 #[derive(Debug, FromEnv)]
@@ -270,3 +272,71 @@ fn test_all_primitives() {
     std::env::remove_var("TESTALLPRIMITIVES_VAL_BOOL");
     std::env::remove_var("TESTALLPRIMITIVES_VAL_STRING");
 }
+
+// Test: FromStr errors from custom types are preserved
+#[derive(Debug)]
+struct Percent(u8);
+
+impl std::str::FromStr for Percent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_suffix('%').and_then(|n| n.parse().ok()) {
+            Some(n) if n <= 100 => Ok(Percent(n)),
+            _ => Err(format!("'{}' is not a percentage", s)),
+        }
+    }
+}
+
+#[derive(Debug, FromEnv)]
+struct TestCustomTypes {
+    addr: std::net::SocketAddr,
+    load: Percent,
+}
+
+#[test]
+fn test_custom_type_parse_error_preserved() {
+    use std::error::Error;
+
+    let err = TestCustomTypes::from_pairs([
+        ("TESTCUSTOMTYPES_ADDR", "not-an-addr"),
+        ("TESTCUSTOMTYPES_LOAD", "50%"),
+    ])
+    .unwrap_err();
+    assert!(matches!(
+        &err,
+        FromEnvError::ParseError {
+            var,
+            source: ParseError::Custom { type_name: "std::net::SocketAddr", .. },
+        } if var == "TESTCUSTOMTYPES_ADDR"
+    ));
+
+    // The original error stays reachable through the source chain
+    let parse_error = err.source().unwrap();
+    let cause = parse_error.source().unwrap();
+    assert!(cause.downcast_ref::<std::net::AddrParseError>().is_some());
+
+    let err = TestCustomTypes::from_pairs([
+        ("TESTCUSTOMTYPES_ADDR", "127.0.0.1:80"),
+        ("TESTCUSTOMTYPES_LOAD", "150%"),
+    ])
+    .unwrap_err();
+    match err {
+        FromEnvError::ParseError {
+            source: ParseError::Custom { type_name, source },
+            ..
+        } => {
+            assert_eq!(type_name, "Percent");
+            assert_eq!(source.to_string(), "'150%' is not a percentage");
+        }
+        other => panic!("Expected a Custom parse error, got {:?}", other),
+    }
+
+    let config = TestCustomTypes::from_pairs([
+        ("TESTCUSTOMTYPES_ADDR", "127.0.0.1:80"),
+        ("TESTCUSTOMTYPES_LOAD", "42%"),
+    ])
+    .unwrap();
+    assert_eq!(config.addr.port(), 80);
+    assert_eq!(config.load.0, 42);
+}
@@ -0,0 +1,135 @@
+use from_env::{FromEnv, FromEnvError, FromEnvTrait, MapEnv, ParseError};
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Debug, FromEnv)]
+struct ServiceConfig {
+    labels: HashMap<String, String>,
+    ports: BTreeMap<String, u16>,
+    limits: Option<HashMap<String, u32>>,
+}
+
+#[test]
+fn test_inline_map() {
+    let config = ServiceConfig::from_pairs([
+        ("SERVICECONFIG_LABELS", "team=core, tier = backend"),
+        ("SERVICECONFIG_PORTS", "http=80,https=443"),
+    ])
+    .unwrap();
+    assert_eq!(config.labels.len(), 2);
+    assert_eq!(config.labels["team"], "core");
+    assert_eq!(config.labels["tier"], "backend");
+    assert_eq!(
+        config.ports.into_iter().collect::<Vec<_>>(),
+        vec![("http".to_string(), 80), ("https".to_string(), 443)]
+    );
+    assert!(config.limits.is_none());
+}
+
+#[test]
+fn test_scanned_map_entries() {
+    let config = ServiceConfig::from_pairs([
+        ("SERVICECONFIG_LABELS_TEAM", "core"),
+        ("SERVICECONFIG_PORTS", "http=80,https=443"),
+        ("SERVICECONFIG_PORTS_HTTP", "8080"),
+        ("SERVICECONFIG_LIMITS_CPU", "2"),
+    ])
+    .unwrap();
+    assert_eq!(config.labels, HashMap::from([("team".to_string(), "core".to_string())]));
+    // Scanned keys are lowercased and win over inline ones
+    assert_eq!(
+        config.ports.into_iter().collect::<Vec<_>>(),
+        vec![("http".to_string(), 8080), ("https".to_string(), 443)]
+    );
+    assert_eq!(config.limits, Some(HashMap::from([("cpu".to_string(), 2)])));
+}
+
+#[test]
+fn test_inline_keys_keep_their_case() {
+    let config = ServiceConfig::from_pairs([
+        ("SERVICECONFIG_LABELS", "X-Request-Id=abc"),
+        ("SERVICECONFIG_PORTS", "HTTP=80"),
+    ])
+    .unwrap();
+    assert_eq!(config.labels, HashMap::from([("X-Request-Id".to_string(), "abc".to_string())]));
+    assert_eq!(config.ports, BTreeMap::from([("HTTP".to_string(), 80)]));
+}
+
+#[test]
+fn test_map_errors() {
+    match ServiceConfig::from_pairs([("SERVICECONFIG_PORTS", "")]) {
+        Err(FromEnvError::MissingVariable(var)) => assert_eq!(var, "SERVICECONFIG_LABELS"),
+        other => panic!("Expected MissingVariable, got {:?}", other),
+    }
+
+    let err = ServiceConfig::from_pairs([("SERVICECONFIG_LABELS", ""), ("SERVICECONFIG_PORTS", "http=80,https")])
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Failed to parse environment variable 'SERVICECONFIG_PORTS': \
+         Failed to parse map entry 'https': expected 'key=value'"
+    );
+
+    match ServiceConfig::from_pairs([("SERVICECONFIG_LABELS", ""), ("SERVICECONFIG_PORTS_HTTP", "eighty")]) {
+        Err(FromEnvError::ParseError {
+            var,
+            source: ParseError::ParseMap { entry, .. },
+        }) => {
+            assert_eq!(var, "SERVICECONFIG_PORTS_HTTP");
+            assert_eq!(entry, "HTTP");
+        }
+        other => panic!("Expected ParseMap, got {:?}", other),
+    }
+}
+
+#[derive(Debug, FromEnv)]
+struct Listener {
+    ports: HashMap<String, u16>,
+    ports_count: u32,
+    ports_tls: Option<TlsSettings>,
+}
+
+#[derive(Debug, FromEnv)]
+struct TlsSettings {
+    cert: String,
+}
+
+#[test]
+fn test_sibling_fields_are_not_entries() {
+    let config = Listener::from_pairs([
+        ("LISTENER_PORTS_HTTP", "80"),
+        ("LISTENER_PORTS_COUNT", "1"),
+        ("LISTENER_PORTS_TLS_CERT", "cert.pem"),
+    ])
+    .unwrap();
+    assert_eq!(config.ports, HashMap::from([("http".to_string(), 80)]));
+    assert_eq!(config.ports_count, 1);
+    assert_eq!(config.ports_tls.unwrap().cert, "cert.pem");
+}
+
+#[derive(Debug, FromEnv)]
+#[from_env(proto3_defaults)]
+struct ProtoLabels {
+    labels: HashMap<String, String>,
+}
+
+#[derive(Debug, FromEnv)]
+struct Deployment {
+    metadata: Option<ProtoLabels>,
+}
+
+#[test]
+fn test_map_proto3_default_and_presence() {
+    let config = ProtoLabels::from_source(&MapEnv::new()).unwrap();
+    assert!(config.labels.is_empty());
+
+    // A scanned entry alone makes the optional nested struct present
+    let config = Deployment::from_pairs([("DEPLOYMENT_METADATA_LABELS_APP", "web")]).unwrap();
+    assert_eq!(config.metadata.unwrap().labels["app"], "web");
+}
+
+#[test]
+fn test_parse_map() {
+    let map: BTreeMap<u8, bool> = from_env::parse_map("1=true, 2=false").unwrap();
+    assert_eq!(map, BTreeMap::from([(1, true), (2, false)]));
+    assert!(from_env::parse_map::<BTreeMap<u8, bool>, _, _>("1=yes").is_err());
+}
@@ -1,4 +1,6 @@
-use from_env::{FromEnv, FromEnvTrait};
+#![allow(clippy::bool_assert_comparison)]
+
+use from_env::{FromEnv, FromEnvError, FromEnvTrait};
 
 // Nested struct for testing
 #[derive(Debug, FromEnv, PartialEq)]
@@ -138,3 +140,77 @@ struct ServerConfigRequired2 {
     disabled: bool,
     listener: Listen,
 }
+
+// Test that the struct-level prefix carries down through several levels
+#[derive(Debug, FromEnv, PartialEq)]
+struct Tls {
+    cert_path: String,
+}
+
+#[derive(Debug, FromEnv, PartialEq)]
+struct SecureListen {
+    port: u32,
+    tls: Tls,
+}
+
+#[derive(Debug, FromEnv, PartialEq)]
+#[from_env(prefix = "APP_")]
+struct DeepConfig {
+    listener: SecureListen,
+}
+
+#[test]
+fn test_nested_struct_multiple_levels() {
+    std::env::set_var("APP_DEEPCONFIG_LISTENER_PORT", "443");
+    std::env::set_var("APP_DEEPCONFIG_LISTENER_TLS_CERT_PATH", "/etc/cert.pem");
+
+    let config = DeepConfig::from_env().unwrap();
+    assert_eq!(config.listener.port, 443);
+    assert_eq!(config.listener.tls.cert_path, "/etc/cert.pem");
+
+    std::env::remove_var("APP_DEEPCONFIG_LISTENER_PORT");
+    std::env::remove_var("APP_DEEPCONFIG_LISTENER_TLS_CERT_PATH");
+}
+
+// Non-FromEnv types still go through FromStr
+#[derive(Debug, FromEnv, PartialEq)]
+struct BindConfig {
+    ip: std::net::IpAddr,
+    fallback_ip: Option<std::net::IpAddr>,
+}
+
+#[test]
+fn test_from_str_field_alongside_nesting() {
+    std::env::set_var("BINDCONFIG_IP", "10.0.0.1");
+    std::env::remove_var("BINDCONFIG_FALLBACK_IP");
+
+    let config = BindConfig::from_env().unwrap();
+    assert_eq!(config.ip, "10.0.0.1".parse::<std::net::IpAddr>().unwrap());
+    assert_eq!(config.fallback_ip, None);
+
+    std::env::remove_var("BINDCONFIG_IP");
+}
+
+// Test optional nested struct with only some variables set
+#[derive(Debug, FromEnv, PartialEq)]
+struct ServerConfigPartial {
+    listener: Option<Listen>,
+}
+
+#[test]
+fn test_nested_struct_optional_partial() {
+    std::env::set_var("SERVERCONFIGPARTIAL_LISTENER_ADDRESS", "0.0.0.0");
+    std::env::remove_var("SERVERCONFIGPARTIAL_LISTENER_PORT");
+
+    let result = ServerConfigPartial::from_env();
+    match result {
+        Err(FromEnvError::PartiallyConfigured { prefix, set, missing }) => {
+            assert_eq!(prefix, "SERVERCONFIGPARTIAL_LISTENER_");
+            assert_eq!(set, vec!["SERVERCONFIGPARTIAL_LISTENER_ADDRESS"]);
+            assert_eq!(missing, vec!["SERVERCONFIGPARTIAL_LISTENER_PORT"]);
+        }
+        other => panic!("Expected PartiallyConfigured, got {:?}", other),
+    }
+
+    std::env::remove_var("SERVERCONFIGPARTIAL_LISTENER_ADDRESS");
+}
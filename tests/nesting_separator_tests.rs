@@ -0,0 +1,109 @@
+use from_env::{FromEnv, FromEnvError, FromEnvTrait};
+use std::collections::HashMap;
+
+#[derive(Debug, FromEnv, PartialEq)]
+struct PoolSettings {
+    max_size: u32,
+}
+
+// No attribute of its own: the separator comes from the struct loaded
+#[derive(Debug, FromEnv, PartialEq)]
+struct Database {
+    host: String,
+    pool: Option<PoolSettings>,
+}
+
+#[derive(Debug, FromEnv, PartialEq)]
+#[from_env(name = "APP", nesting_separator = "__")]
+struct AppConfig {
+    log_level: String,
+    database: Database,
+    replicas: Vec<Database>,
+    labels: HashMap<String, String>,
+}
+
+#[test]
+fn test_double_underscore() {
+    let config = AppConfig::from_pairs([
+        ("APP__LOG_LEVEL", "info"),
+        ("APP__DATABASE__HOST", "db.local"),
+        ("APP__DATABASE__POOL__MAX_SIZE", "16"),
+        ("APP__REPLICAS__0__HOST", "replica-0"),
+        ("APP__REPLICAS__1__HOST", "replica-1"),
+        ("APP__LABELS__TEAM", "core"),
+    ])
+    .unwrap();
+
+    assert_eq!(config.log_level, "info");
+    assert_eq!(
+        config.database,
+        Database {
+            host: "db.local".to_string(),
+            pool: Some(PoolSettings { max_size: 16 }),
+        }
+    );
+    let replicas: Vec<&str> = config.replicas.iter().map(|r| r.host.as_str()).collect();
+    assert_eq!(replicas, ["replica-0", "replica-1"]);
+    assert_eq!(config.labels.get("team").map(String::as_str), Some("core"));
+}
+
+#[test]
+fn test_single_underscore_is_not_nesting() {
+    // `APP_DATABASE_HOST` no longer reaches the nested struct
+    let result = AppConfig::from_pairs([
+        ("APP__LOG_LEVEL", "info"),
+        ("APP_DATABASE_HOST", "db.local"),
+    ]);
+    assert!(matches!(result, Err(FromEnvError::MissingVariable(var)) if var == "APP__DATABASE__HOST"));
+}
+
+#[test]
+fn test_indexed_count() {
+    let result = AppConfig::from_pairs([
+        ("APP__LOG_LEVEL", "info"),
+        ("APP__DATABASE__HOST", "db.local"),
+        ("APP__REPLICAS__COUNT", "1"),
+        ("APP__REPLICAS__0__HOST", "replica-0"),
+        ("APP__REPLICAS__2__HOST", "replica-2"),
+    ]);
+    assert!(matches!(
+        result,
+        Err(FromEnvError::IndexOutOfRange { prefix, index: 2, count: 1 }) if prefix == "APP__REPLICAS__"
+    ));
+}
+
+#[derive(Debug, FromEnv, PartialEq)]
+struct Tls {
+    cert: String,
+}
+
+#[derive(Debug, FromEnv, PartialEq)]
+struct Listener {
+    tls: Tls,
+}
+
+#[derive(Debug, FromEnv, PartialEq)]
+#[from_env(name = "APP", nesting_separator = "__")]
+struct DoubleConfig {
+    listener: Listener,
+}
+
+#[derive(Debug, FromEnv, PartialEq)]
+#[from_env(name = "APP", nesting_separator = "-")]
+struct DashConfig {
+    listener: Listener,
+}
+
+#[test]
+fn test_separator_is_inherited() {
+    // Every level below the loaded struct uses its separator
+    let config = DoubleConfig::from_pairs([("APP__LISTENER__TLS__CERT", "double.pem")]).unwrap();
+    assert_eq!(config.listener.tls.cert, "double.pem");
+
+    let config = DashConfig::from_pairs([("APP-LISTENER-TLS-CERT", "dash.pem")]).unwrap();
+    assert_eq!(config.listener.tls.cert, "dash.pem");
+
+    // Loaded on its own, `Listener` uses the default
+    let listener = Listener::from_pairs([("LISTENER_TLS_CERT", "plain.pem")]).unwrap();
+    assert_eq!(listener.tls.cert, "plain.pem");
+}
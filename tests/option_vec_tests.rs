@@ -1,3 +1,5 @@
+#![allow(clippy::approx_constant)]
+
 use from_env::{FromEnv, FromEnvTrait};
 
 // Test 1: Option with value
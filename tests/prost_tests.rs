@@ -0,0 +1,232 @@
+//! Types written the way prost-build generates them, with `FromEnv` added the
+//! way `type_attribute` would.
+
+use from_env::{FromEnv, FromEnvError, FromEnvTrait, ParseError};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration, FromEnv)]
+#[repr(i32)]
+pub enum LogLevel {
+    Unspecified = 0,
+    Info = 1,
+    Debug = 2,
+}
+
+impl LogLevel {
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Unspecified => "LOG_LEVEL_UNSPECIFIED",
+            Self::Info => "LOG_LEVEL_INFO",
+            Self::Debug => "LOG_LEVEL_DEBUG",
+        }
+    }
+
+    pub fn from_str_name(value: &str) -> Option<Self> {
+        match value {
+            "LOG_LEVEL_UNSPECIFIED" => Some(Self::Unspecified),
+            "LOG_LEVEL_INFO" => Some(Self::Info),
+            "LOG_LEVEL_DEBUG" => Some(Self::Debug),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message, FromEnv)]
+pub struct LogConfig {
+    #[prost(enumeration = "LogLevel", tag = "1")]
+    pub level: i32,
+    #[prost(enumeration = "LogLevel", optional, tag = "2")]
+    pub fallback_level: ::core::option::Option<i32>,
+    #[prost(enumeration = "LogLevel", repeated, tag = "3")]
+    pub extra_levels: ::prost::alloc::vec::Vec<i32>,
+    #[prost(string, tag = "4")]
+    pub target: ::prost::alloc::string::String,
+}
+
+#[test]
+fn test_enumeration_by_name() {
+    for value in ["DEBUG", "debug", "LOG_LEVEL_DEBUG", "log_level_debug", "2"] {
+        let config = LogConfig::from_pairs([
+            ("LOGCONFIG_LEVEL", value),
+            ("LOGCONFIG_EXTRA_LEVELS", ""),
+            ("LOGCONFIG_TARGET", "app"),
+        ])
+        .unwrap();
+        assert_eq!(config.level, LogLevel::Debug as i32, "value {}", value);
+        assert_eq!(config.fallback_level, None);
+    }
+}
+
+#[test]
+fn test_enumeration_option_and_vec() {
+    let config = LogConfig::from_pairs([
+        ("LOGCONFIG_LEVEL", "info"),
+        ("LOGCONFIG_FALLBACK_LEVEL", "Debug"),
+        ("LOGCONFIG_EXTRA_LEVELS", "info, 2, LOG_LEVEL_UNSPECIFIED"),
+        ("LOGCONFIG_TARGET", "app"),
+    ])
+    .unwrap();
+    assert_eq!(config.level(), LogLevel::Info);
+    assert_eq!(config.fallback_level(), LogLevel::Debug);
+    assert_eq!(config.extra_levels, vec![1, 2, 0]);
+}
+
+#[test]
+fn test_enumeration_error_lists_valid_names() {
+    let err = LogConfig::from_pairs([
+        ("LOGCONFIG_LEVEL", "verbose"),
+        ("LOGCONFIG_EXTRA_LEVELS", ""),
+        ("LOGCONFIG_TARGET", "app"),
+    ])
+    .unwrap_err();
+    match &err {
+        FromEnvError::ParseError {
+            source: ParseError::InvalidEnum { enum_name, valid, .. },
+            ..
+        } => {
+            assert_eq!(*enum_name, "LogLevel");
+            assert_eq!(
+                valid,
+                &vec!["LOG_LEVEL_UNSPECIFIED", "LOG_LEVEL_INFO", "LOG_LEVEL_DEBUG"]
+            );
+        }
+        other => panic!("Expected InvalidEnum, got {:?}", other),
+    }
+    assert_eq!(
+        err.to_string(),
+        "Failed to parse environment variable 'LOGCONFIG_LEVEL': Failed to parse LogLevel: \
+         expected a number or one of [LOG_LEVEL_UNSPECIFIED, LOG_LEVEL_INFO, LOG_LEVEL_DEBUG], got 'verbose'"
+    );
+
+    // Numbers must be a known variant too
+    assert!(LogConfig::from_pairs([
+        ("LOGCONFIG_LEVEL", "7"),
+        ("LOGCONFIG_EXTRA_LEVELS", ""),
+        ("LOGCONFIG_TARGET", "app"),
+    ])
+    .is_err());
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration, FromEnv)]
+#[repr(i32)]
+pub enum Region {
+    Unspecified = 0,
+    Mars = 100000,
+}
+
+impl Region {
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Unspecified => "REGION_UNSPECIFIED",
+            Self::Mars => "REGION_MARS",
+        }
+    }
+
+    pub fn from_str_name(value: &str) -> Option<Self> {
+        match value {
+            "REGION_UNSPECIFIED" => Some(Self::Unspecified),
+            "REGION_MARS" => Some(Self::Mars),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message, FromEnv)]
+pub struct RegionConfig {
+    #[prost(enumeration = "Region", tag = "1")]
+    pub region: i32,
+}
+
+#[test]
+fn test_enumeration_large_values() {
+    let config = RegionConfig::from_pairs([("REGIONCONFIG_REGION", "mars")]).unwrap();
+    assert_eq!(config.region(), Region::Mars);
+
+    // Every variant is listed, however far from zero
+    let err = RegionConfig::from_pairs([("REGIONCONFIG_REGION", "venus")]).unwrap_err();
+    assert!(matches!(
+        err,
+        FromEnvError::ParseError { source: ParseError::InvalidEnum { valid, .. }, .. }
+            if valid == ["REGION_UNSPECIFIED", "REGION_MARS"]
+    ));
+}
+
+#[derive(Clone, PartialEq, prost::Message, FromEnv)]
+pub struct PostgresConfig {
+    #[prost(string, tag = "1")]
+    pub host: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "2")]
+    pub port: u32,
+}
+
+#[derive(Clone, PartialEq, prost::Message, FromEnv)]
+pub struct AppConfig {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(oneof = "app_config::Kind", tags = "2, 3")]
+    pub kind: ::core::option::Option<app_config::Kind>,
+}
+
+pub mod app_config {
+    use from_env::FromEnv;
+
+    #[derive(Clone, PartialEq, prost::Oneof, FromEnv)]
+    pub enum Kind {
+        #[prost(message, tag = "2")]
+        Postgres(super::PostgresConfig),
+        #[prost(string, tag = "3")]
+        SqlitePath(::prost::alloc::string::String),
+    }
+}
+
+#[test]
+fn test_oneof_message_variant() {
+    let config = AppConfig::from_pairs([
+        ("APPCONFIG_NAME", "svc"),
+        ("APPCONFIG_KIND", "postgres"),
+        ("APPCONFIG_KIND_POSTGRES_HOST", "db.internal"),
+        ("APPCONFIG_KIND_POSTGRES_PORT", "5432"),
+    ])
+    .unwrap();
+    assert_eq!(
+        config.kind,
+        Some(app_config::Kind::Postgres(PostgresConfig {
+            host: "db.internal".to_string(),
+            port: 5432,
+        }))
+    );
+}
+
+#[test]
+fn test_oneof_scalar_variant_and_unset() {
+    let config = AppConfig::from_pairs([
+        ("APPCONFIG_NAME", "svc"),
+        ("APPCONFIG_KIND", "Sqlite-Path"),
+        ("APPCONFIG_KIND_SQLITE_PATH", "/var/lib/app.db"),
+    ])
+    .unwrap();
+    assert_eq!(
+        config.kind,
+        Some(app_config::Kind::SqlitePath("/var/lib/app.db".to_string()))
+    );
+
+    let config = AppConfig::from_pairs([("APPCONFIG_NAME", "svc")]).unwrap();
+    assert_eq!(config.kind, None);
+}
+
+#[test]
+fn test_oneof_errors() {
+    let err = AppConfig::from_pairs([("APPCONFIG_NAME", "svc"), ("APPCONFIG_KIND", "mysql")]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Failed to parse environment variable 'APPCONFIG_KIND': Failed to parse Kind: \
+         expected one of [POSTGRES, SQLITE_PATH], got 'mysql'"
+    );
+
+    match AppConfig::from_pairs([("APPCONFIG_NAME", "svc"), ("APPCONFIG_KIND", "postgres")]) {
+        Err(FromEnvError::MissingVariable(var)) => assert_eq!(var, "APPCONFIG_KIND_POSTGRES_HOST"),
+        other => panic!("Expected MissingVariable, got {:?}", other),
+    }
+
+    let errors = AppConfig::from_source_all_errors(&from_env::MapEnv::from([("APPCONFIG_KIND", "mysql")])).unwrap_err();
+    assert_eq!(errors.len(), 2);
+}
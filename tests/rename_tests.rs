@@ -0,0 +1,102 @@
+#![allow(clippy::upper_case_acronyms)]
+
+use from_env::{FromEnv, FromEnvTrait};
+
+#[derive(Debug, FromEnv, PartialEq)]
+#[from_env(rename_all = "SCREAMING_SNAKE")]
+struct HTTPServer2Config {
+    max_conns: u32,
+    tls_cert_path: Option<String>,
+}
+
+#[test]
+fn test_screaming_snake_acronyms_and_digits() {
+    let config = HTTPServer2Config::from_pairs([
+        ("HTTP_SERVER2_CONFIG_MAX_CONNS", "64"),
+        ("HTTP_SERVER2_CONFIG_TLS_CERT_PATH", "/etc/tls.pem"),
+    ])
+    .unwrap();
+    assert_eq!(
+        config,
+        HTTPServer2Config {
+            max_conns: 64,
+            tls_cert_path: Some("/etc/tls.pem".to_string()),
+        }
+    );
+}
+
+#[derive(Debug, FromEnv, PartialEq)]
+#[from_env(rename_all = "UPPERFLAT")]
+struct PoolSettings {
+    max_conns: u32,
+}
+
+#[test]
+fn test_upperflat_fields() {
+    let config = PoolSettings::from_pairs([("POOLSETTINGS_MAXCONNS", "8")]).unwrap();
+    assert_eq!(config.max_conns, 8);
+}
+
+#[derive(Debug, FromEnv, PartialEq)]
+#[from_env(prefix = "app-", no_struct_name, rename_all = "kebab")]
+struct KebabConfig {
+    max_conns: u32,
+    log_level: String,
+}
+
+#[test]
+fn test_kebab() {
+    let config = KebabConfig::from_pairs([("app-max-conns", "4"), ("app-log-level", "warn")]).unwrap();
+    assert_eq!(config.max_conns, 4);
+    assert_eq!(config.log_level, "warn");
+}
+
+#[derive(Debug, FromEnv, PartialEq)]
+#[from_env(rename_all = "snake")]
+struct DbSettings {
+    host_name: String,
+}
+
+#[derive(Debug, FromEnv, PartialEq)]
+#[from_env(rename_all = "snake")]
+struct ServiceConfig {
+    primary_db: DbSettings,
+}
+
+#[test]
+fn test_nested_segments() {
+    // The parent spells its own segments; the nested struct spells its fields
+    let config = ServiceConfig::from_pairs([("service_config_primary_db_host_name", "db.local")]).unwrap();
+    assert_eq!(config.primary_db.host_name, "db.local");
+}
+
+#[derive(Debug, FromEnv, PartialEq)]
+#[from_env(rename_all = "kebab")]
+struct KebabDatabase {
+    host_name: String,
+}
+
+#[derive(Debug, FromEnv, PartialEq)]
+#[from_env(rename_all = "kebab")]
+struct KebabService {
+    primary_db: KebabDatabase,
+}
+
+#[test]
+fn test_kebab_joins_segments_with_dashes() {
+    let config = KebabService::from_pairs([("kebab-service-primary-db-host-name", "db.local")]).unwrap();
+    assert_eq!(config.primary_db.host_name, "db.local");
+}
+
+#[derive(Debug, FromEnv, PartialEq)]
+#[from_env(word_separator = "_")]
+struct HTTPProxy {
+    url: String,
+}
+
+#[test]
+fn test_word_separator_keeps_acronyms_whole() {
+    // Only `rename_all` splits acronyms; `word_separator` keeps its old names
+    let config = HTTPProxy::from_pairs([("HTTPPROXY_URL", "http://proxy:3128")]).unwrap();
+    assert_eq!(config.url, "http://proxy:3128");
+}
@@ -1,3 +1,5 @@
+#![allow(clippy::bool_assert_comparison)]
+
 use from_env::{FromEnv, FromEnvTrait};
 
 #[derive(Debug, FromEnv)]
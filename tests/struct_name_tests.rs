@@ -0,0 +1,70 @@
+use from_env::{FromEnv, FromEnvTrait, MapEnv};
+
+#[derive(Debug, FromEnv, PartialEq)]
+#[from_env(name = "DB")]
+struct DatabaseConfig {
+    host: String,
+    port: u16,
+}
+
+#[test]
+fn test_name_replaces_struct_segment() {
+    let config = DatabaseConfig::from_pairs([("DB_HOST", "db.local"), ("DB_PORT", "5432")]).unwrap();
+    assert_eq!(
+        config,
+        DatabaseConfig {
+            host: "db.local".to_string(),
+            port: 5432,
+        }
+    );
+
+    // The default name is no longer read
+    assert!(DatabaseConfig::from_pairs([("DATABASECONFIG_HOST", "db.local"), ("DATABASECONFIG_PORT", "5432")]).is_err());
+}
+
+#[derive(Debug, FromEnv, PartialEq)]
+#[from_env(prefix = "APP_", no_struct_name)]
+struct AppConfig {
+    host: String,
+    database: Option<DatabaseConfig>,
+}
+
+#[test]
+fn test_no_struct_name_with_prefix() {
+    let config = AppConfig::from_pairs([("APP_HOST", "example.com")]).unwrap();
+    assert_eq!(config.host, "example.com");
+    assert_eq!(config.database, None);
+}
+
+#[test]
+fn test_no_struct_name_nested() {
+    // Nested structs load under the field name, not their own `name`
+    let config = AppConfig::from_pairs([
+        ("APP_HOST", "example.com"),
+        ("APP_DATABASE_HOST", "db.local"),
+        ("APP_DATABASE_PORT", "5432"),
+    ])
+    .unwrap();
+    assert_eq!(
+        config.database,
+        Some(DatabaseConfig {
+            host: "db.local".to_string(),
+            port: 5432,
+        })
+    );
+}
+
+#[derive(Debug, FromEnv, PartialEq)]
+#[from_env(no_struct_name)]
+struct Bare {
+    log_level: String,
+}
+
+#[test]
+fn test_no_struct_name_with_runtime_prefix() {
+    assert_eq!(Bare::from_pairs([("LOG_LEVEL", "debug")]).unwrap().log_level, "debug");
+
+    let source: MapEnv = [("SVC_LOG_LEVEL", "info")].into_iter().collect();
+    let config = Bare::from_source_with_prefix(&source, "SVC_").unwrap();
+    assert_eq!(config.log_level, "info");
+}
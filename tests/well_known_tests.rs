@@ -0,0 +1,92 @@
+use from_env::{FromEnv, FromEnvError, FromEnvTrait, MapEnv, ParseError};
+use prost_types::{Duration, Timestamp};
+
+fn duration(seconds: i64, nanos: i32) -> Duration {
+    Duration { seconds, nanos }
+}
+
+#[test]
+fn test_parse_duration() {
+    assert_eq!(from_env::parse_duration("30s").unwrap(), duration(30, 0));
+    assert_eq!(from_env::parse_duration("1h30m").unwrap(), duration(5400, 0));
+    assert_eq!(from_env::parse_duration("1h 30m 15s").unwrap(), duration(5415, 0));
+    assert_eq!(from_env::parse_duration("1.5h").unwrap(), duration(5400, 0));
+    assert_eq!(from_env::parse_duration("250ms").unwrap(), duration(0, 250_000_000));
+    assert_eq!(from_env::parse_duration("1.000000001s").unwrap(), duration(1, 1));
+    assert_eq!(from_env::parse_duration("2d").unwrap(), duration(172_800, 0));
+    assert_eq!(from_env::parse_duration("-1.5s").unwrap(), duration(-1, -500_000_000));
+    assert_eq!(from_env::parse_duration("0").unwrap(), duration(0, 0));
+
+    for bad in ["", "30", "s", "10x", "1..5s", "-", "99999999999999d"] {
+        assert!(
+            matches!(from_env::parse_duration(bad), Err(ParseError::InvalidDuration { .. })),
+            "{:?} should not parse",
+            bad
+        );
+    }
+}
+
+#[test]
+fn test_parse_timestamp() {
+    assert_eq!(
+        from_env::parse_timestamp("2024-05-01T12:30:00Z").unwrap(),
+        Timestamp { seconds: 1_714_566_600, nanos: 0 }
+    );
+    assert_eq!(
+        from_env::parse_timestamp("2024-05-01T14:30:00.5+02:00").unwrap(),
+        Timestamp { seconds: 1_714_566_600, nanos: 500_000_000 }
+    );
+    assert!(matches!(
+        from_env::parse_timestamp("yesterday"),
+        Err(ParseError::InvalidTimestamp { .. })
+    ));
+}
+
+#[derive(Debug, FromEnv)]
+struct JobConfig {
+    timeout: Duration,
+    retry_delay: Option<Duration>,
+    backoff: Vec<Duration>,
+    not_before: Option<Timestamp>,
+    checkpoints: Vec<Timestamp>,
+    // A `google.protobuf.StringValue` in prost
+    owner: Option<String>,
+}
+
+#[test]
+fn test_well_known_fields() {
+    let config = JobConfig::from_pairs([
+        ("JOBCONFIG_TIMEOUT", "1h30m"),
+        ("JOBCONFIG_BACKOFF", "1s, 5s, 30s"),
+        ("JOBCONFIG_NOT_BEFORE", "2024-05-01T12:30:00Z"),
+        ("JOBCONFIG_CHECKPOINTS", ""),
+    ])
+    .unwrap();
+    assert_eq!(config.timeout, duration(5400, 0));
+    assert_eq!(config.retry_delay, None);
+    assert_eq!(config.backoff, vec![duration(1, 0), duration(5, 0), duration(30, 0)]);
+    assert_eq!(config.not_before.unwrap().seconds, 1_714_566_600);
+    assert!(config.checkpoints.is_empty());
+    assert_eq!(config.owner, None);
+}
+
+#[test]
+fn test_well_known_errors() {
+    let env = MapEnv::from([
+        ("JOBCONFIG_TIMEOUT", "soon"),
+        ("JOBCONFIG_RETRY_DELAY", "5 minutes"),
+        ("JOBCONFIG_BACKOFF", "1s"),
+        ("JOBCONFIG_CHECKPOINTS", "2024-05-01T12:30:00Z, never"),
+    ]);
+
+    match JobConfig::from_source(&env) {
+        Err(FromEnvError::ParseError {
+            var,
+            source: ParseError::InvalidDuration { .. },
+        }) => assert_eq!(var, "JOBCONFIG_TIMEOUT"),
+        other => panic!("Expected InvalidDuration, got {:?}", other),
+    }
+
+    let errors = JobConfig::from_source_all_errors(&env).unwrap_err();
+    assert_eq!(errors.len(), 3);
+}